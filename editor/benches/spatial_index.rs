@@ -0,0 +1,111 @@
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use editor::{RgGeometry, RgSpatialIndex};
+use rand::Rng;
+
+//************************************************************************** Bench **********************************************************************************//
+// 运行: cargo bench --bench spatial_index
+const QUERY_COUNT: usize = 1_000;
+
+fn main() {
+    for node_count in [10_000, 100_000] {
+        bench_node_count(node_count);
+    }
+}
+
+fn bench_node_count(node_count: usize) {
+    let mut rng = rand::thread_rng();
+
+    // 节点密度与 generate_nodes 相近：每个节点平均占用约 400x400 的区域
+    let extent = (node_count as f32).sqrt() * 400.0;
+    let rects: Vec<RgGeometry> = (0..node_count)
+        .map(|_| {
+            (
+                rng.gen_range(0.0..extent),
+                rng.gen_range(0.0..extent),
+                rng.gen_range(80.0..200.0),
+                rng.gen_range(60.0..150.0),
+            )
+        })
+        .collect();
+    let points: Vec<(f32, f32)> = (0..QUERY_COUNT)
+        .map(|_| (rng.gen_range(0.0..extent), rng.gen_range(0.0..extent)))
+        .collect();
+    let viewports: Vec<RgGeometry> = (0..QUERY_COUNT)
+        .map(|_| (rng.gen_range(0.0..extent), rng.gen_range(0.0..extent), 1600.0, 1200.0))
+        .collect();
+
+    println!("== {node_count} nodes");
+
+    let mut index = RgSpatialIndex::default();
+    let elapsed = measure(|| {
+        for (id, rect) in rects.iter().enumerate() {
+            index.insert(id as u64, *rect);
+        }
+    });
+    report("build", node_count, elapsed);
+
+    let elapsed = measure(|| {
+        for (x, y) in &points {
+            black_box(index.query_point(*x, *y, 8.0));
+        }
+    });
+    report("point query", QUERY_COUNT, elapsed);
+
+    let elapsed = measure(|| {
+        for (x, y) in &points {
+            black_box(linear_point_query(&rects, *x, *y, 8.0));
+        }
+    });
+    report("point query (linear)", QUERY_COUNT, elapsed);
+
+    let elapsed = measure(|| {
+        for viewport in &viewports {
+            black_box(index.query_rect(*viewport));
+        }
+    });
+    report("rect query", QUERY_COUNT, elapsed);
+
+    let elapsed = measure(|| {
+        for viewport in &viewports {
+            black_box(linear_rect_query(&rects, *viewport));
+        }
+    });
+    report("rect query (linear)", QUERY_COUNT, elapsed);
+
+    let elapsed = measure(|| {
+        for (id, (x, y)) in points.iter().enumerate() {
+            let (_, _, width, height) = rects[id];
+            index.insert(id as u64, (*x, *y, width, height));
+        }
+    });
+    report("update", QUERY_COUNT, elapsed);
+}
+
+fn linear_point_query(rects: &[RgGeometry], x: f32, y: f32, margin: f32) -> Vec<u64> {
+    rects.iter()
+        .enumerate()
+        .filter(|(_, r)| x >= r.0 - margin && x <= r.0 + r.2 + margin && y >= r.1 - margin && y <= r.1 + r.3 + margin)
+        .map(|(id, _)| id as u64)
+        .collect()
+}
+
+fn linear_rect_query(rects: &[RgGeometry], rect: RgGeometry) -> Vec<u64> {
+    rects.iter()
+        .enumerate()
+        .filter(|(_, r)| r.0 < rect.0 + rect.2 && r.0 + r.2 > rect.0 && r.1 < rect.1 + rect.3 && r.1 + r.3 > rect.1)
+        .map(|(id, _)| id as u64)
+        .collect()
+}
+
+fn measure(f: impl FnOnce()) -> Duration {
+    let start = Instant::now();
+    f();
+    start.elapsed()
+}
+
+fn report(name: &str, iterations: usize, elapsed: Duration) {
+    let per_iteration = elapsed / iterations.max(1) as u32;
+    println!("{name:<24} total {elapsed:>12.3?}  per op {per_iteration:>10.3?}");
+}
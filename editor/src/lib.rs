@@ -1,8 +1,36 @@
+mod rg_connector;
+mod rg_document;
 mod rg_editor;
+mod rg_grid;
+mod rg_group;
+mod rg_guides;
+mod rg_history;
+mod rg_image;
+mod rg_layout;
+mod rg_minimap;
 mod rg_rect;
+mod rg_router;
+mod rg_shape;
+mod rg_spatial;
+mod rg_style;
+mod rg_text_edit;
 
+pub use rg_connector::*;
+pub use rg_document::*;
 pub use rg_editor::*;
+pub use rg_grid::*;
+pub use rg_group::*;
+pub use rg_guides::*;
+pub use rg_history::*;
+pub use rg_image::*;
+pub use rg_layout::*;
+pub use rg_minimap::*;
 pub use rg_rect::*;
+pub use rg_router::*;
+pub use rg_shape::*;
+pub use rg_spatial::*;
+pub use rg_style::*;
+pub use rg_text_edit::*;
 
 use rand::Rng;
 
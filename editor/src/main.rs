@@ -3,6 +3,8 @@ use gpui::*;
 
 fn main() {
     Application::new().run(|cx: &mut App| {
+        RgEditor::bind_keys(cx);
+
         let mut window_size = size(px(1600.0), px(1200.0));
         if let Some(display) = cx.primary_display() {
             let display_size = display.bounds().size;
@@ -0,0 +1,259 @@
+use gpui::*;
+use serde::{Deserialize, Serialize};
+
+use crate::*;
+
+//************************************************************************** RgConnector **********************************************************************************//
+// 正交路径离开锚点时先沿锚点方向延伸的距离（世界坐标）
+const CONNECTOR_STUB: f32 = 20.0;
+const BEZIER_SEGMENTS: usize = 24;
+// 箭头尺寸（屏幕像素）
+const ARROW_LENGTH: f32 = 12.0;
+const ARROW_WIDTH: f32 = 8.0;
+
+/// 连线在节点上的连接位置
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RgAnchor {
+    /// 从中心指向另一端，连接点取在边框上
+    #[default]
+    Center,
+    Top,
+    Right,
+    Bottom,
+    Left,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RgConnectorRoute {
+    #[default]
+    Straight,
+    Orthogonal,
+    Bezier,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RgConnectorData {
+    pub id: u64,
+    pub source: u64,
+    #[serde(default)]
+    pub source_anchor: RgAnchor,
+    pub target: u64,
+    #[serde(default)]
+    pub target_anchor: RgAnchor,
+    #[serde(default)]
+    pub route: RgConnectorRoute,
+    #[serde(default)]
+    pub start_arrow: bool,
+    #[serde(default = "default_end_arrow")]
+    pub end_arrow: bool,
+    #[serde(default)]
+    pub label: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct RgConnector {
+    pub data: RgConnectorData,
+    pub selected: bool,
+}
+
+//************************************************************************** Trait **********************************************************************************//
+impl RgAnchor {
+    /// 锚点的世界坐标和朝外方向，toward 为连线另一端，只用于 Center
+    pub fn resolve(&self, node: &RgRect, toward: (f32, f32)) -> ((f32, f32), (f32, f32)) {
+        let half_width = node.width / 2.0;
+        let half_height = node.height / 2.0;
+        let center = (node.x + half_width, node.y + half_height);
+        let (sin, cos) = node.rotation.to_radians().sin_cos();
+        let rotate = |(x, y): (f32, f32)| (x * cos - y * sin, x * sin + y * cos);
+
+        // 节点局部坐标系（未旋转，原点为中心）中的位置和方向
+        let (local, normal) = match self {
+            RgAnchor::Top => ((0.0, -half_height), (0.0, -1.0)),
+            RgAnchor::Right => ((half_width, 0.0), (1.0, 0.0)),
+            RgAnchor::Bottom => ((0.0, half_height), (0.0, 1.0)),
+            RgAnchor::Left => ((-half_width, 0.0), (-1.0, 0.0)),
+            RgAnchor::Center => {
+                let (dx, dy) = rotate_back(toward.0 - center.0, toward.1 - center.1, sin, cos);
+                if dx == 0.0 && dy == 0.0 {
+                    return (center, (0.0, 0.0));
+                }
+                let scale_x = if dx != 0.0 { half_width / dx.abs() } else { f32::INFINITY };
+                let scale_y = if dy != 0.0 { half_height / dy.abs() } else { f32::INFINITY };
+                let normal = if scale_x <= scale_y { (dx.signum(), 0.0) } else { (0.0, dy.signum()) };
+                let scale = scale_x.min(scale_y);
+                ((dx * scale, dy * scale), normal)
+            }
+        };
+
+        let (x, y) = rotate(local);
+        ((center.0 + x, center.1 + y), rotate(normal))
+    }
+}
+
+impl RgConnector {
+    pub fn new(data: RgConnectorData) -> Self {
+        Self { data, selected: false }
+    }
+
+    pub fn id(&self) -> u64 {
+        self.data.id
+    }
+
+    pub fn is_attached_to(&self, node_id: u64) -> bool {
+        self.data.source == node_id || self.data.target == node_id
+    }
+
+    /// 两端锚点的 (位置, 朝外方向)（世界坐标）
+    pub fn endpoints(&self, source: &RgRect, target: &RgRect) -> (((f32, f32), (f32, f32)), ((f32, f32), (f32, f32))) {
+        let source_center = (source.x + source.width / 2.0, source.y + source.height / 2.0);
+        let target_center = (target.x + target.width / 2.0, target.y + target.height / 2.0);
+        (
+            self.data.source_anchor.resolve(source, target_center),
+            self.data.target_anchor.resolve(target, source_center),
+        )
+    }
+
+    /// 连线经过的折线（世界坐标），贝塞尔曲线按固定段数展开。
+    /// 正交连线在这里只做简单转折，避开其他节点的路径由 RgOrthogonalRouter 计算
+    pub fn route_points(&self, source: &RgRect, target: &RgRect) -> Vec<(f32, f32)> {
+        let ((start, start_normal), (end, end_normal)) = self.endpoints(source, target);
+
+        match self.data.route {
+            RgConnectorRoute::Straight => vec![start, end],
+            RgConnectorRoute::Orthogonal => orthogonal_route(start, start_normal, end, end_normal),
+            RgConnectorRoute::Bezier => bezier_route(start, start_normal, end, end_normal),
+        }
+    }
+
+    /// 点到折线的距离是否在 tolerance 以内
+    pub fn hit_test(points: &[(f32, f32)], point: (f32, f32), tolerance: f32) -> bool {
+        points.windows(2).any(|segment| distance_to_segment(point, segment[0], segment[1]) <= tolerance)
+    }
+
+    /// 折线按长度计算的中点，用于放置标签
+    pub fn midpoint(points: &[(f32, f32)]) -> Option<(f32, f32)> {
+        let length: f32 = points.windows(2).map(|s| distance(s[0], s[1])).sum();
+        let mut remaining = length / 2.0;
+
+        for segment in points.windows(2) {
+            let segment_length = distance(segment[0], segment[1]);
+            if segment_length >= remaining && segment_length > 0.0 {
+                let t = remaining / segment_length;
+                return Some((
+                    segment[0].0 + (segment[1].0 - segment[0].0) * t,
+                    segment[0].1 + (segment[1].1 - segment[0].1) * t,
+                ));
+            }
+            remaining -= segment_length;
+        }
+
+        points.first().copied()
+    }
+
+    /// 绘制折线和箭头，points 为相对 bounds 的屏幕坐标
+    pub fn paint(&self, points: &[Point<Pixels>], bounds: Bounds<Pixels>, window: &mut Window) {
+        if points.len() < 2 {
+            return;
+        }
+
+        let (color, width) = if self.selected {
+            (rgb(0x008aff), 2.5)
+        } else {
+            (rgb(0x505050), 1.5)
+        };
+
+        let mut line = PathBuilder::stroke(px(width));
+        line.move_to(bounds.origin + points[0]);
+        for point in &points[1..] {
+            line.line_to(bounds.origin + *point);
+        }
+        if let Ok(path) = line.build() {
+            window.paint_path(path, color);
+        }
+
+        let last = points.len() - 1;
+        if self.data.end_arrow {
+            paint_arrow(bounds.origin + points[last - 1], bounds.origin + points[last], color, window);
+        }
+        if self.data.start_arrow {
+            paint_arrow(bounds.origin + points[1], bounds.origin + points[0], color, window);
+        }
+    }
+}
+
+fn default_end_arrow() -> bool {
+    true
+}
+
+fn rotate_back(x: f32, y: f32, sin: f32, cos: f32) -> (f32, f32) {
+    (x * cos + y * sin, -x * sin + y * cos)
+}
+
+fn distance(a: (f32, f32), b: (f32, f32)) -> f32 {
+    ((b.0 - a.0).powi(2) + (b.1 - a.1).powi(2)).sqrt()
+}
+
+// 先沿锚点方向延伸，再在两端中间转折
+fn orthogonal_route(start: (f32, f32), start_normal: (f32, f32), end: (f32, f32), end_normal: (f32, f32)) -> Vec<(f32, f32)> {
+    let a = (start.0 + start_normal.0 * CONNECTOR_STUB, start.1 + start_normal.1 * CONNECTOR_STUB);
+    let b = (end.0 + end_normal.0 * CONNECTOR_STUB, end.1 + end_normal.1 * CONNECTOR_STUB);
+
+    let horizontal = if start_normal != (0.0, 0.0) {
+        start_normal.0.abs() >= start_normal.1.abs()
+    } else {
+        (b.0 - a.0).abs() >= (b.1 - a.1).abs()
+    };
+
+    let mut points = vec![start, a];
+    if horizontal {
+        let middle = (a.0 + b.0) / 2.0;
+        points.push((middle, a.1));
+        points.push((middle, b.1));
+    } else {
+        let middle = (a.1 + b.1) / 2.0;
+        points.push((a.0, middle));
+        points.push((b.0, middle));
+    }
+    points.push(b);
+    points.push(end);
+
+    points.dedup();
+    points
+}
+
+fn bezier_route(start: (f32, f32), start_normal: (f32, f32), end: (f32, f32), end_normal: (f32, f32)) -> Vec<(f32, f32)> {
+    let strength = (distance(start, end) / 3.0).max(30.0);
+    let control_a = (start.0 + start_normal.0 * strength, start.1 + start_normal.1 * strength);
+    let control_b = (end.0 + end_normal.0 * strength, end.1 + end_normal.1 * strength);
+
+    (0..=BEZIER_SEGMENTS)
+        .map(|i| {
+            let t = i as f32 / BEZIER_SEGMENTS as f32;
+            let u = 1.0 - t;
+            let (a, b, c, d) = (u * u * u, 3.0 * u * u * t, 3.0 * u * t * t, t * t * t);
+            (
+                a * start.0 + b * control_a.0 + c * control_b.0 + d * end.0,
+                a * start.1 + b * control_a.1 + c * control_b.1 + d * end.1,
+            )
+        })
+        .collect()
+}
+
+fn paint_arrow(from: Point<Pixels>, to: Point<Pixels>, color: Rgba, window: &mut Window) {
+    let dx = (to.x - from.x).to_f64() as f32;
+    let dy = (to.y - from.y).to_f64() as f32;
+    let length = (dx * dx + dy * dy).sqrt();
+    if length == 0.0 {
+        return;
+    }
+    let (ux, uy) = (dx / length, dy / length);
+
+    let base = point(to.x - px(ux * ARROW_LENGTH), to.y - px(uy * ARROW_LENGTH));
+    let offset = point(px(-uy * ARROW_WIDTH / 2.0), px(ux * ARROW_WIDTH / 2.0));
+
+    let mut arrow = PathBuilder::fill();
+    arrow.add_polygon(&[to, base + offset, base - offset], true);
+    if let Ok(path) = arrow.build() {
+        window.paint_path(path, color);
+    }
+}
@@ -0,0 +1,314 @@
+use std::fmt;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::*;
+
+//************************************************************************** RgDocument **********************************************************************************//
+/// 当前文档格式版本，格式变化时递增，并在 `RgDocument::migrate` 中补充旧版本的升级逻辑
+pub const RG_DOCUMENT_VERSION: u32 = 1;
+
+/// 剪贴板数据格式标识，用于从纯文本中识别编辑器节点数据
+pub const RG_CLIPBOARD_FORMAT: &str = "rg-editor/nodes";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RgDocument {
+    pub version: u32,
+    pub content_bounds: (f32, f32, f32, f32),
+    #[serde(default)]
+    pub content_bounds_mode: RgContentBoundsMode,
+    #[serde(default)]
+    pub view: RgViewState,
+    #[serde(default)]
+    pub nodes: Vec<RgNodeData>,
+    #[serde(default)]
+    pub groups: Vec<RgGroupData>,
+    #[serde(default)]
+    pub connectors: Vec<RgConnectorData>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RgViewState {
+    pub zoom: f32,
+    pub pan: (f32, f32),
+    #[serde(default)]
+    pub user_zoomed: bool,
+}
+
+/// 内容区域（虚线框）的确定方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum RgContentBoundsMode {
+    /// 固定区域，由 RgEditor::set_content_bounds 设置
+    #[default]
+    Fixed,
+    /// 所有节点包围盒的并集向外扩展 padding（世界坐标），节点增删、移动或调整大小后自动更新。没有节点时保持原区域
+    Auto { padding: f32 },
+    /// 固定画板，移动、调整大小和旋转节点时限制在区域内
+    Artboard,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RgNodeData {
+    pub id: u64,
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+    /// 绕中心顺时针旋转的角度（度）
+    #[serde(default)]
+    pub rotation: f32,
+    #[serde(default)]
+    pub z_order: usize,
+    #[serde(default)]
+    pub shape: RgShape,
+    #[serde(default)]
+    pub style: RgStyle,
+    /// 为 None 时使用默认标签 "Rect {id}"
+    #[serde(default)]
+    pub label: Option<String>,
+    /// 图片节点的图片路径或嵌入的文件内容
+    #[serde(default)]
+    pub image: Option<RgImage>,
+}
+
+/// 组的直接子节点 id 和直接子组 id
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RgGroupData {
+    pub id: u64,
+    #[serde(default)]
+    pub nodes: Vec<u64>,
+    #[serde(default)]
+    pub groups: Vec<u64>,
+}
+
+/// 复制到剪贴板的节点数据，z_order 为节点在复制集合中的相对顺序，只包含两端都被复制的连线
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RgClipboardData {
+    pub format: String,
+    pub version: u32,
+    pub nodes: Vec<RgNodeData>,
+    #[serde(default)]
+    pub groups: Vec<RgGroupData>,
+    #[serde(default)]
+    pub connectors: Vec<RgConnectorData>,
+}
+
+#[derive(Debug)]
+pub enum RgDocumentError {
+    Io(std::io::Error),
+    Json(serde_json::Error),
+    /// 版本号不是非负整数
+    InvalidVersion,
+    /// 版本号比当前支持的版本新
+    UnsupportedVersion(u64),
+}
+
+//************************************************************************** Trait **********************************************************************************//
+impl RgDocument {
+    pub fn to_json(&self) -> Result<String, RgDocumentError> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, RgDocumentError> {
+        let value: serde_json::Value = serde_json::from_str(json)?;
+        let version = match value.get("version") {
+            // 加入版本号之前保存的文档按第一版处理
+            None => 1,
+            Some(version) => {
+                let version = version.as_u64().ok_or(RgDocumentError::InvalidVersion)?;
+                u32::try_from(version)
+                    .ok()
+                    .filter(|version| *version <= RG_DOCUMENT_VERSION)
+                    .ok_or(RgDocumentError::UnsupportedVersion(version))?
+            }
+        };
+
+        let mut value = Self::migrate(value, version);
+        if let Some(object) = value.as_object_mut() {
+            object.insert("version".to_string(), RG_DOCUMENT_VERSION.into());
+        }
+        let mut document: RgDocument = serde_json::from_value(value)?;
+        document.version = RG_DOCUMENT_VERSION;
+        document.nodes.sort_by_key(|node| node.z_order);
+
+        Ok(document)
+    }
+
+    pub fn write_to_file(&self, path: impl AsRef<Path>) -> Result<(), RgDocumentError> {
+        std::fs::write(path, self.to_json()?)?;
+        Ok(())
+    }
+
+    pub fn read_from_file(path: impl AsRef<Path>) -> Result<Self, RgDocumentError> {
+        let json = std::fs::read_to_string(path)?;
+        Self::from_json(&json)
+    }
+
+    // 将旧版本文档逐级升级到当前版本。第一版之后新增的字段都有默认值，目前不需要转换
+    fn migrate(value: serde_json::Value, _version: u32) -> serde_json::Value {
+        value
+    }
+}
+
+impl RgClipboardData {
+    pub fn new(nodes: Vec<RgNodeData>, groups: Vec<RgGroupData>, connectors: Vec<RgConnectorData>) -> Self {
+        Self {
+            format: RG_CLIPBOARD_FORMAT.to_string(),
+            version: RG_DOCUMENT_VERSION,
+            nodes,
+            groups,
+            connectors,
+        }
+    }
+
+    pub fn to_json(&self) -> Result<String, RgDocumentError> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// 解析剪贴板文本，格式标识不符或版本过新时返回 None
+    pub fn from_json(json: &str) -> Option<Self> {
+        let data: RgClipboardData = serde_json::from_str(json).ok()?;
+        (data.format == RG_CLIPBOARD_FORMAT && data.version <= RG_DOCUMENT_VERSION).then_some(data)
+    }
+}
+
+impl Default for RgViewState {
+    fn default() -> Self {
+        Self {
+            zoom: 1.0,
+            pan: (0.0, 0.0),
+            user_zoomed: false,
+        }
+    }
+}
+
+impl fmt::Display for RgDocumentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RgDocumentError::Io(err) => write!(f, "failed to access document file: {err}"),
+            RgDocumentError::Json(err) => write!(f, "invalid document json: {err}"),
+            RgDocumentError::InvalidVersion => write!(f, "document version is not a non-negative integer"),
+            RgDocumentError::UnsupportedVersion(version) => {
+                write!(f, "document version {version} is newer than supported version {RG_DOCUMENT_VERSION}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for RgDocumentError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            RgDocumentError::Io(err) => Some(err),
+            RgDocumentError::Json(err) => Some(err),
+            RgDocumentError::InvalidVersion | RgDocumentError::UnsupportedVersion(_) => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for RgDocumentError {
+    fn from(err: std::io::Error) -> Self {
+        RgDocumentError::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for RgDocumentError {
+    fn from(err: serde_json::Error) -> Self {
+        RgDocumentError::Json(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 只包含第一版字段的文档
+    const V1_DOCUMENT: &str = r#"{
+        "version": 1,
+        "content_bounds": [0.0, 0.0, 800.0, 600.0],
+        "nodes": [
+            { "id": 2, "x": 300.0, "y": 40.0, "width": 80.0, "height": 60.0, "z_order": 1 },
+            { "id": 1, "x": 10.0, "y": 20.0, "width": 100.0, "height": 50.0, "z_order": 0 }
+        ]
+    }"#;
+
+    fn full_document() -> RgDocument {
+        let mut document = RgDocument::from_json(V1_DOCUMENT).unwrap();
+        document.content_bounds_mode = RgContentBoundsMode::Auto { padding: 40.0 };
+        document.view = RgViewState { zoom: 1.5, pan: (-20.0, 35.0), user_zoomed: true };
+        document.nodes[0].rotation = 30.0;
+        document.nodes[0].shape = RgShape::Polygon { points: vec![(0.0, 0.0), (1.0, 0.5), (0.0, 1.0)] };
+        document.nodes[1].label = Some("标签".to_string());
+        document.groups = vec![RgGroupData { id: 0, nodes: vec![1, 2], groups: Vec::new() }];
+        document
+    }
+
+    #[test]
+    fn v1_document_without_new_fields_loads() {
+        let document = RgDocument::from_json(V1_DOCUMENT).unwrap();
+        assert_eq!(document.version, RG_DOCUMENT_VERSION);
+        assert_eq!(document.content_bounds_mode, RgContentBoundsMode::Fixed);
+        assert_eq!(document.view, RgViewState::default());
+        assert_eq!(document.nodes.iter().map(|node| node.id).collect::<Vec<_>>(), vec![1, 2]);
+
+        let node = &document.nodes[0];
+        assert_eq!((node.x, node.y, node.width, node.height), (10.0, 20.0, 100.0, 50.0));
+        assert_eq!(node.rotation, 0.0);
+        assert_eq!(node.shape, RgShape::Rectangle);
+        assert_eq!(node.style, RgStyle::default());
+        assert_eq!(node.label, None);
+        assert_eq!(node.image, None);
+        assert!(document.groups.is_empty());
+        assert!(document.connectors.is_empty());
+    }
+
+    #[test]
+    fn document_round_trips() {
+        let document = full_document();
+        assert_eq!(RgDocument::from_json(&document.to_json().unwrap()).unwrap(), document);
+
+        let path = std::env::temp_dir().join(format!("rg-document-{}.json", std::process::id()));
+        document.write_to_file(&path).unwrap();
+        let loaded = RgDocument::read_from_file(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.unwrap(), document);
+    }
+
+    #[test]
+    fn missing_version_loads_as_first_version() {
+        let json = V1_DOCUMENT.replace(r#""version": 1,"#, "");
+        assert_eq!(RgDocument::from_json(&json).unwrap(), RgDocument::from_json(V1_DOCUMENT).unwrap());
+    }
+
+    #[test]
+    fn newer_or_invalid_version_is_rejected() {
+        let with_version = |version: &str| V1_DOCUMENT.replace(r#""version": 1"#, &format!(r#""version": {version}"#));
+
+        let newer = (RG_DOCUMENT_VERSION + 1) as u64;
+        assert!(matches!(
+            RgDocument::from_json(&with_version(&newer.to_string())),
+            Err(RgDocumentError::UnsupportedVersion(version)) if version == newer
+        ));
+        // 超出 u32 范围的版本号不能被截断成旧版本
+        let overflow = (1u64 << 32) + 1;
+        assert!(matches!(
+            RgDocument::from_json(&with_version(&overflow.to_string())),
+            Err(RgDocumentError::UnsupportedVersion(version)) if version == overflow
+        ));
+        for invalid in ["-1", "1.5", r#""1""#, "null"] {
+            assert!(matches!(RgDocument::from_json(&with_version(invalid)), Err(RgDocumentError::InvalidVersion)), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn clipboard_data_checks_format_and_version() {
+        let document = full_document();
+        let data = RgClipboardData::new(document.nodes.clone(), document.groups.clone(), Vec::new());
+        assert_eq!(RgClipboardData::from_json(&data.to_json().unwrap()), Some(data.clone()));
+
+        let other_format = RgClipboardData { format: "other".to_string(), ..data.clone() };
+        assert_eq!(RgClipboardData::from_json(&other_format.to_json().unwrap()), None);
+        let newer = RgClipboardData { version: RG_DOCUMENT_VERSION + 1, ..data };
+        assert_eq!(RgClipboardData::from_json(&newer.to_json().unwrap()), None);
+    }
+}
@@ -1,7 +1,120 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
 use gpui::*;
 use crate::*;
 
+actions!(
+    rg_editor,
+    [
+        Undo,
+        Redo,
+        NudgeLeft,
+        NudgeRight,
+        NudgeUp,
+        NudgeDown,
+        NudgeLeftLarge,
+        NudgeRightLarge,
+        NudgeUpLarge,
+        NudgeDownLarge,
+        DeleteSelection,
+        SelectAll,
+        ClearSelection,
+        SelectNext,
+        SelectPrevious,
+        CopyNodes,
+        CutNodes,
+        PasteNodes,
+        PasteNodesAtCursor,
+        DuplicateNodes,
+        GroupSelection,
+        UngroupSelection,
+        AlignLeft,
+        AlignHorizontalCenter,
+        AlignRight,
+        AlignTop,
+        AlignVerticalCenter,
+        AlignBottom,
+        DistributeHorizontalSpacing,
+        DistributeVerticalSpacing,
+        DistributeHorizontalCenters,
+        DistributeVerticalCenters,
+        ZoomIn,
+        ZoomOut,
+        ZoomToActualSize,
+        ZoomToFit,
+        ZoomToSelection,
+    ]
+);
+
+const MIN_ZOOM: f32 = 0.1;
+const MAX_ZOOM: f32 = 10.0;
+/// 放大/缩小命令依次切换的缩放比例
+pub const ZOOM_PRESETS: [f32; 12] = [0.1, 0.25, 0.5, 0.75, 1.0, 1.5, 2.0, 3.0, 4.0, 6.0, 8.0, 10.0];
+// 适配视图时内容与视口边缘的留白（屏幕像素）
+const FIT_PADDING: f32 = 40.0;
+const VIEW_ANIMATION_DURATION: Duration = Duration::from_millis(250);
+const WHEEL_ZOOM_SPEED: f32 = 0.002;
+const PAN_MARGIN: f32 = 64.0;
+const NUDGE_STEP: f32 = 1.0;
+const NUDGE_STEP_LARGE: f32 = 10.0;
+// 与 RgRect::detect_handle_at 中的边缘检测范围一致（屏幕像素），并覆盖上边缘外的旋转手柄
+const HIT_MARGIN: f32 = 8.0 + ROTATE_HANDLE_DISTANCE;
+const PASTE_OFFSET: f32 = 10.0;
+// 连线点选范围（屏幕像素）
+const CONNECTOR_HIT_TOLERANCE: f32 = 6.0;
+// 正交连线路由时，在两端包围盒外额外考虑障碍物的范围（世界坐标）
+const ROUTE_SEARCH_MARGIN: f32 = 200.0;
+// 多选包围盒手柄大小（屏幕像素）
+const GROUP_HANDLE_SIZE: f32 = 10.0;
+const LAYOUT_ANIMATION_DURATION: Duration = Duration::from_millis(300);
+// 拖入图片文件时创建的节点大小（世界坐标）
+const IMAGE_NODE_SIZE: (f32, f32) = (200.0, 150.0);
+
 //************************************************************************** RgEditor **********************************************************************************//
+/// RgEditor 对外发出的事件，每次完整操作（一次拖动、一次按键、一次撤销等）只发出一次
+#[derive(Debug, Clone, PartialEq)]
+pub enum RgEditorEvent {
+    SelectionChanged { before: Vec<u64>, after: Vec<u64> },
+    /// (节点 id, 旧几何数据, 新几何数据)
+    NodesMoved { changes: Vec<(u64, RgGeometry, RgGeometry)> },
+    NodesResized { changes: Vec<(u64, RgGeometry, RgGeometry)> },
+    /// (节点 id, 旧角度, 新角度)
+    NodesRotated { changes: Vec<(u64, f32, f32)> },
+    /// (节点 id, 旧样式, 新样式)
+    NodesRestyled { changes: Vec<(u64, RgStyle, RgStyle)> },
+    /// (节点 id, 旧标签, 新标签)
+    NodesRelabeled { changes: Vec<(u64, String, String)> },
+    /// 节点 id 按 z 序从下到上排列
+    NodesReordered { before: Vec<u64>, after: Vec<u64> },
+    NodesAdded { nodes: Vec<RgNodeData> },
+    NodesRemoved { nodes: Vec<RgNodeData> },
+    NodesGrouped { group: RgGroupData },
+    NodesUngrouped { group: RgGroupData },
+    ConnectorsAdded { connectors: Vec<RgConnectorData> },
+    ConnectorsRemoved { connectors: Vec<RgConnectorData> },
+}
+
+// 多选整体缩放开始时的包围盒，以及每个节点的初始几何数据
+struct RgGroupResize {
+    bounds: RgGeometry,
+    nodes: Vec<(Entity<RgRect>, RgGeometry)>,
+}
+
+// 自动布局的过渡动画：(节点, 起始位置, 目标位置)
+struct RgLayoutAnimation {
+    start: Instant,
+    nodes: Vec<(Entity<RgRect>, (f32, f32), (f32, f32))>,
+}
+
+// 视图的过渡动画：缩放按比例插值，视口中心的世界坐标线性插值
+struct RgViewAnimation {
+    start: Instant,
+    from: (f32, Point<Pixels>),
+    to: (f32, Point<Pixels>),
+}
+
 pub struct RgEditor {
     bounds: Bounds<Pixels>,
     nodes: Vec<Entity<RgRect>>,
@@ -10,12 +123,46 @@ pub struct RgEditor {
     pan: Point<Pixels>,
     is_selecting: bool,
     drag_state: Option<(Vec<Entity<RgRect>>, ResizeHandle)>,
+    group_resize: Option<RgGroupResize>,
     selection_rect: Option<(Point<Pixels>, Point<Pixels>)>,
     click_start_position: Option<Point<Pixels>>,
     content_bounds: (f32, f32, f32, f32),
+    content_bounds_mode: RgContentBoundsMode,
     view_initialized: bool,
     is_updating_bounds: bool,
     user_zoomed: bool,
+    focus_handle: FocusHandle,
+    history: RgHistory,
+    transaction_start: Option<(Vec<u64>, Vec<u64>)>,
+    drag_start_geometry: Vec<(u64, RgGeometry, f32)>,
+    pan_drag_start: Option<(Point<Pixels>, Point<Pixels>)>,
+    space_pressed: bool,
+    grid: RgGrid,
+    smart_guides: RgSmartGuides,
+    minimap: RgMinimap,
+    // 拖动缩略图期间固定映射，避免视口移动引起缩略图比例变化
+    minimap_drag: Option<RgMinimapTransform>,
+    // 缩略图节点列表缓存，节点几何（写入空间索引时）、层级或选择变化后清除
+    minimap_nodes: Option<Arc<Vec<(RgGeometry, bool)>>>,
+    guides: Vec<RgGuide>,
+    spatial_index: RgSpatialIndex,
+    node_lookup: HashMap<u64, (usize, Entity<RgRect>)>,
+    marquee_selection: HashSet<u64>,
+    last_mouse_position: Option<Point<Pixels>>,
+    paste_count: u32,
+    groups: RgGroups,
+    // 双击进入的组，点击时选择该组的直接成员
+    entered_group: Option<u64>,
+    connectors: Vec<RgConnector>,
+    router: RgOrthogonalRouter,
+    // 已计算的正交连线路径缓存，节点移动后只清除受影响的连线
+    routed_connectors: HashMap<u64, Vec<(f32, f32)>>,
+    layout_animation: Option<RgLayoutAnimation>,
+    view_animation: Option<RgViewAnimation>,
+    // 对齐时作为参照的关键对象，在多选中再次点击已选中的节点时设置
+    key_node: Option<u64>,
+    // 正在原位编辑标签的编辑器及其事件订阅
+    text_edit: Option<(Entity<RgTextEditor>, Subscription)>,
 }
 
 //************************************************************************** Trait **********************************************************************************//
@@ -31,7 +178,7 @@ impl RgEditor {
             node_entities.push(cx.new(|_| node));
         }
 
-        Self {
+        let mut editor = Self {
             bounds: Bounds::default(),
             nodes: node_entities,
             zoom,
@@ -40,19 +187,299 @@ impl RgEditor {
 
             is_selecting: false,
             drag_state: None,
+            group_resize: None,
             selection_rect: None,
             click_start_position: None,
             content_bounds: (0.0, 0.0, 800.0, 600.0),
+            content_bounds_mode: RgContentBoundsMode::Fixed,
             view_initialized: false,
             is_updating_bounds: false,
             user_zoomed: false,
+            focus_handle: cx.focus_handle(),
+            history: RgHistory::default(),
+            transaction_start: None,
+            drag_start_geometry: Vec::new(),
+            pan_drag_start: None,
+            space_pressed: false,
+            grid: RgGrid::default(),
+            smart_guides: RgSmartGuides::default(),
+            minimap: RgMinimap::default(),
+            minimap_drag: None,
+            minimap_nodes: None,
+            guides: Vec::new(),
+            spatial_index: RgSpatialIndex::default(),
+            node_lookup: HashMap::new(),
+            marquee_selection: HashSet::new(),
+            last_mouse_position: None,
+            paste_count: 0,
+            groups: RgGroups::new(),
+            entered_group: None,
+            connectors: Vec::new(),
+            router: RgOrthogonalRouter::default(),
+            routed_connectors: HashMap::new(),
+            layout_animation: None,
+            view_animation: None,
+            key_node: None,
+            text_edit: None,
+        };
+
+        editor.rebuild_node_index(cx);
+        editor
+    }
+
+    /// 重建 id 查找表和空间索引。宿主直接修改了节点几何数据后需要调用
+    pub fn rebuild_node_index(&mut self, cx: &App) {
+        self.rebuild_node_lookup(cx);
+        self.routed_connectors.clear();
+
+        self.spatial_index.clear();
+        for node_entity in &self.nodes {
+            let node = node_entity.read(cx);
+            self.spatial_index.insert(node.id, node.world_bounds());
+        }
+    }
+
+    // 节点列表或层级变化后重建 id -> (z 序下标, 节点) 查找表
+    fn rebuild_node_lookup(&mut self, cx: &App) {
+        self.minimap_nodes = None;
+        self.node_lookup = self.nodes.iter()
+            .enumerate()
+            .map(|(index, node_entity)| (node_entity.read(cx).id, (index, node_entity.clone())))
+            .collect();
+    }
+
+    fn update_spatial_index(&mut self, nodes: &[Entity<RgRect>], cx: &App) {
+        self.minimap_nodes = None;
+        for node_entity in nodes {
+            let node = node_entity.read(cx);
+            self.spatial_index.insert(node.id, node.world_bounds());
+        }
+    }
+
+    fn screen_to_world(&self, position: Point<Pixels>) -> (f32, f32) {
+        (
+            (position.x.to_f64() as f32 - self.pan.x.to_f64() as f32) / self.zoom,
+            (position.y.to_f64() as f32 - self.pan.y.to_f64() as f32) / self.zoom,
+        )
+    }
+
+    // 当前视口对应的世界坐标矩形
+    fn visible_world_rect(&self) -> RgGeometry {
+        let (x, y) = self.screen_to_world(point(px(0.0), px(0.0)));
+        (
+            x,
+            y,
+            self.base_size.width.to_f64() as f32 / self.zoom,
+            self.base_size.height.to_f64() as f32 / self.zoom,
+        )
+    }
+
+    pub fn bind_keys(cx: &mut App) {
+        cx.bind_keys([
+            KeyBinding::new("secondary-z", Undo, Some("RgEditor")),
+            KeyBinding::new("secondary-shift-z", Redo, Some("RgEditor")),
+            KeyBinding::new("left", NudgeLeft, Some("RgEditor")),
+            KeyBinding::new("right", NudgeRight, Some("RgEditor")),
+            KeyBinding::new("up", NudgeUp, Some("RgEditor")),
+            KeyBinding::new("down", NudgeDown, Some("RgEditor")),
+            KeyBinding::new("shift-left", NudgeLeftLarge, Some("RgEditor")),
+            KeyBinding::new("shift-right", NudgeRightLarge, Some("RgEditor")),
+            KeyBinding::new("shift-up", NudgeUpLarge, Some("RgEditor")),
+            KeyBinding::new("shift-down", NudgeDownLarge, Some("RgEditor")),
+            KeyBinding::new("delete", DeleteSelection, Some("RgEditor")),
+            KeyBinding::new("backspace", DeleteSelection, Some("RgEditor")),
+            KeyBinding::new("secondary-a", SelectAll, Some("RgEditor")),
+            KeyBinding::new("escape", ClearSelection, Some("RgEditor")),
+            KeyBinding::new("tab", SelectNext, Some("RgEditor")),
+            KeyBinding::new("shift-tab", SelectPrevious, Some("RgEditor")),
+            KeyBinding::new("secondary-c", CopyNodes, Some("RgEditor")),
+            KeyBinding::new("secondary-x", CutNodes, Some("RgEditor")),
+            KeyBinding::new("secondary-v", PasteNodes, Some("RgEditor")),
+            KeyBinding::new("secondary-shift-v", PasteNodesAtCursor, Some("RgEditor")),
+            KeyBinding::new("secondary-d", DuplicateNodes, Some("RgEditor")),
+            KeyBinding::new("secondary-g", GroupSelection, Some("RgEditor")),
+            KeyBinding::new("secondary-shift-g", UngroupSelection, Some("RgEditor")),
+            KeyBinding::new("alt-a", AlignLeft, Some("RgEditor")),
+            KeyBinding::new("alt-h", AlignHorizontalCenter, Some("RgEditor")),
+            KeyBinding::new("alt-d", AlignRight, Some("RgEditor")),
+            KeyBinding::new("alt-w", AlignTop, Some("RgEditor")),
+            KeyBinding::new("alt-v", AlignVerticalCenter, Some("RgEditor")),
+            KeyBinding::new("alt-s", AlignBottom, Some("RgEditor")),
+            KeyBinding::new("alt-shift-h", DistributeHorizontalSpacing, Some("RgEditor")),
+            KeyBinding::new("alt-shift-v", DistributeVerticalSpacing, Some("RgEditor")),
+            KeyBinding::new("secondary-alt-h", DistributeHorizontalCenters, Some("RgEditor")),
+            KeyBinding::new("secondary-alt-v", DistributeVerticalCenters, Some("RgEditor")),
+            KeyBinding::new("secondary-=", ZoomIn, Some("RgEditor")),
+            KeyBinding::new("secondary--", ZoomOut, Some("RgEditor")),
+            KeyBinding::new("secondary-0", ZoomToActualSize, Some("RgEditor")),
+            KeyBinding::new("secondary-1", ZoomToFit, Some("RgEditor")),
+            KeyBinding::new("secondary-2", ZoomToSelection, Some("RgEditor")),
+        ]);
+        RgTextEditor::bind_keys(cx);
+    }
+
+    pub fn to_document(&self, cx: &App) -> RgDocument {
+        let nodes = self.nodes.iter()
+            .enumerate()
+            .map(|(z_order, node_entity)| node_entity.read(cx).to_node_data(z_order))
+            .collect();
+
+        RgDocument {
+            version: RG_DOCUMENT_VERSION,
+            content_bounds: self.content_bounds,
+            content_bounds_mode: self.content_bounds_mode,
+            view: RgViewState {
+                zoom: self.zoom,
+                pan: (self.pan.x.to_f64() as f32, self.pan.y.to_f64() as f32),
+                user_zoomed: self.user_zoomed,
+            },
+            nodes,
+            groups: self.groups.to_data(|id| self.node_lookup.contains_key(&id)),
+            connectors: self.connectors.iter()
+                .filter(|connector| {
+                    self.node_lookup.contains_key(&connector.data.source) && self.node_lookup.contains_key(&connector.data.target)
+                })
+                .map(|connector| connector.data.clone())
+                .collect(),
+        }
+    }
+
+    pub fn load_document(&mut self, document: &RgDocument, cx: &mut Context<Self>) {
+        let mut nodes = document.nodes.clone();
+        nodes.sort_by_key(|node| node.z_order);
+
+        self.nodes = nodes.iter()
+            .map(|data| cx.new(|_| RgRect::from_node_data(data)))
+            .collect();
+        self.content_bounds = document.content_bounds;
+        self.content_bounds_mode = document.content_bounds_mode;
+        self.rebuild_node_index(cx);
+        self.update_auto_content_bounds(cx);
+        self.groups.load(&document.groups);
+        self.entered_group = None;
+        self.connectors = document.connectors.iter().cloned().map(RgConnector::new).collect();
+
+        self.is_selecting = false;
+        self.drag_state = None;
+        self.group_resize = None;
+        self.layout_animation = None;
+        self.view_animation = None;
+        self.text_edit = None;
+        self.selection_rect = None;
+        self.click_start_position = None;
+        self.pan_drag_start = None;
+        self.transaction_start = None;
+        self.drag_start_geometry.clear();
+        self.history.clear();
+
+        self.user_zoomed = document.view.user_zoomed;
+        if self.user_zoomed {
+            self.zoom = document.view.zoom.clamp(MIN_ZOOM, MAX_ZOOM);
+            self.pan = point(px(document.view.pan.0), px(document.view.pan.1));
+            self.view_initialized = true;
+        } else {
+            self.adjust_view_to_fit_content_bounds();
         }
+
+        self.update_nodes_pan_zoom(cx);
+        cx.refresh_windows();
+    }
+
+    pub fn save_to_file(&self, path: impl AsRef<std::path::Path>, cx: &App) -> Result<(), RgDocumentError> {
+        self.to_document(cx).write_to_file(path)
+    }
+
+    pub fn load_from_file(&mut self, path: impl AsRef<std::path::Path>, cx: &mut Context<Self>) -> Result<(), RgDocumentError> {
+        let document = RgDocument::read_from_file(path)?;
+        self.load_document(&document, cx);
+        Ok(())
     }
 
+    /// 设置内容区域（画板模式下即画板），自动模式下会在节点变化后被重新计算
     pub fn set_content_bounds(&mut self, min_x: f32, min_y: f32, max_x: f32, max_y: f32) {
         self.content_bounds = (min_x, min_y, max_x, max_y);
     }
 
+    /// (min_x, min_y, max_x, max_y)
+    pub fn content_bounds(&self) -> (f32, f32, f32, f32) {
+        self.content_bounds
+    }
+
+    pub fn content_bounds_mode(&self) -> RgContentBoundsMode {
+        self.content_bounds_mode
+    }
+
+    /// 切换到画板模式不会移动已有的节点，之后移动或调整大小时才限制在画板内
+    pub fn set_content_bounds_mode(&mut self, mode: RgContentBoundsMode, cx: &mut Context<Self>) {
+        self.content_bounds_mode = mode;
+        self.update_auto_content_bounds(cx);
+        cx.refresh_windows();
+    }
+
+    // 自动模式下按所有节点的包围盒重新计算内容区域
+    fn update_auto_content_bounds(&mut self, cx: &mut Context<Self>) {
+        let RgContentBoundsMode::Auto { padding } = self.content_bounds_mode else {
+            return;
+        };
+        let nodes = self.nodes.clone();
+        if let Some((x, y, width, height)) = Self::union_geometry(&nodes, cx) {
+            self.content_bounds = (x - padding, y - padding, x + width + padding, y + height + padding);
+        }
+    }
+
+    // 把包围盒移回画板内所需的偏移，包围盒比画板大时与画板左上角对齐
+    fn artboard_offset((min_x, min_y, max_x, max_y): (f32, f32, f32, f32), (x, y, width, height): RgGeometry) -> (f32, f32) {
+        let axis = |start: f32, size: f32, min: f32, max: f32| {
+            if start < min || size > max - min {
+                min - start
+            } else if start + size > max {
+                max - start - size
+            } else {
+                0.0
+            }
+        };
+        (axis(x, width, min_x, max_x), axis(y, height, min_y, max_y))
+    }
+
+    // 画板模式下把拖动中的节点限制在画板内：整体移动时一起平移回画板内；
+    // 调整大小时裁掉未旋转节点超出画板的部分，旋转或保持宽高比的节点平移回画板内
+    fn constrain_to_artboard(&self, nodes: &[Entity<RgRect>], handle: ResizeHandle, options: RgDragOptions, cx: &mut Context<Self>) {
+        if self.content_bounds_mode != RgContentBoundsMode::Artboard {
+            return;
+        }
+        let artboard = self.content_bounds;
+
+        if handle == ResizeHandle::Body {
+            if let Some(bounds) = Self::union_geometry(nodes, cx) {
+                let (dx, dy) = Self::artboard_offset(artboard, bounds);
+                self.offset_nodes(nodes, dx, dy, cx);
+            }
+            return;
+        }
+
+        let (min_x, min_y, max_x, max_y) = artboard;
+        for node_entity in nodes {
+            cx.update_entity(node_entity, |node, _| {
+                if handle != ResizeHandle::Rotate && node.rotation == 0.0 && !node.keeps_aspect_ratio(options) {
+                    let left = node.x.max(min_x);
+                    let top = node.y.max(min_y);
+                    let right = (node.x + node.width).min(max_x);
+                    let bottom = (node.y + node.height).min(max_y);
+                    if right - left >= MIN_NODE_SIZE && bottom - top >= MIN_NODE_SIZE {
+                        node.x = left;
+                        node.y = top;
+                        node.width = right - left;
+                        node.height = bottom - top;
+                    }
+                }
+
+                let (dx, dy) = Self::artboard_offset(artboard, node.world_bounds());
+                node.x += dx;
+                node.y += dy;
+            });
+        }
+    }
+
     fn update_base_size(&mut self, new_bounds: Bounds<Pixels>) {
         if self.is_updating_bounds {
             return;
@@ -81,7 +508,7 @@ impl RgEditor {
                 scale_x.min(scale_y)
             } else {
                 1.0
-            }.clamp(0.1, 4.0);
+            }.clamp(MIN_ZOOM, MAX_ZOOM);
 
             self.zoom = new_zoom;
 
@@ -97,15 +524,305 @@ impl RgEditor {
         }
     }
 
+    // 只同步可见节点，视口外的节点在重新可见或开始拖动时再同步
     fn update_nodes_pan_zoom(&mut self, cx: &mut Context<Self>) {
-        for n in &self.nodes {
-            let pan = self.pan;
-            let zoom = self.zoom;
+        let visible_nodes = self.get_visible_nodes(cx);
+        self.sync_nodes_pan_zoom(&visible_nodes, cx);
+    }
+
+    fn sync_nodes_pan_zoom(&self, nodes: &[Entity<RgRect>], cx: &mut Context<Self>) {
+        let zoom = self.zoom;
+        let pan = (self.pan.x.to_f64() as f32, self.pan.y.to_f64() as f32);
+
+        for n in nodes {
             cx.update_entity(n, move |node, _| {
                 node.zoom = zoom;
-                node.pan = (pan.x.to_f64() as f32, pan.y.to_f64() as f32);
+                node.pan = pan;
+            });
+        }
+    }
+
+    pub fn grid(&self) -> &RgGrid {
+        &self.grid
+    }
+
+    pub fn set_grid(&mut self, grid: RgGrid, cx: &mut Context<Self>) {
+        self.grid = grid;
+        cx.refresh_windows();
+    }
+
+    pub fn smart_guides(&self) -> &RgSmartGuides {
+        &self.smart_guides
+    }
+
+    pub fn set_smart_guides(&mut self, smart_guides: RgSmartGuides, cx: &mut Context<Self>) {
+        self.smart_guides = smart_guides;
+        cx.refresh_windows();
+    }
+
+    pub fn minimap(&self) -> &RgMinimap {
+        &self.minimap
+    }
+
+    pub fn set_minimap(&mut self, minimap: RgMinimap, cx: &mut Context<Self>) {
+        self.minimap = minimap;
+        self.minimap_drag = None;
+        cx.refresh_windows();
+    }
+
+    // 缩略图中显示的节点包围盒和选中状态（世界坐标），只在缓存被清除后重新生成
+    fn minimap_nodes(&mut self, cx: &App) -> Arc<Vec<(RgGeometry, bool)>> {
+        if let Some(nodes) = &self.minimap_nodes {
+            return nodes.clone();
+        }
+
+        let extent = self.spatial_index.extent().unwrap_or_default();
+        let nodes = Arc::new(RgMinimap::merge_nodes(
+            extent,
+            self.nodes.iter().filter_map(|node_entity| {
+                let node = node_entity.read(cx);
+                Some((self.spatial_index.get(node.id)?, node.selected))
+            }),
+        ));
+        self.minimap_nodes = Some(nodes.clone());
+        nodes
+    }
+
+    // 缩略图覆盖内容区域、所有节点和当前视口
+    fn minimap_transform(&self) -> RgMinimapTransform {
+        let (min_x, min_y, max_x, max_y) = self.content_bounds;
+        let regions = self.spatial_index.extent()
+            .into_iter()
+            .chain([(min_x, min_y, max_x - min_x, max_y - min_y), self.visible_world_rect()]);
+        self.minimap.transform(self.minimap.panel_bounds(self.bounds.size).size, regions)
+    }
+
+    fn hit_minimap(&self, position: Point<Pixels>) -> bool {
+        self.minimap.visible && self.minimap.panel_bounds(self.bounds.size).contains(&position)
+    }
+
+    // 平移视图，使缩略图中 position（画布坐标）对应的世界坐标位于视口中心
+    fn center_view_on_minimap(&mut self, position: Point<Pixels>, transform: RgMinimapTransform, cx: &mut Context<Self>) {
+        let panel = self.minimap.panel_bounds(self.bounds.size);
+        let local = position - panel.origin;
+        let center = transform.to_world((local.x.to_f64() as f32, local.y.to_f64() as f32));
+        let pan = self.pan_centered_on(center, self.zoom);
+        self.transition_view(self.zoom, pan, Some((center.0, center.1, 0.0, 0.0)), false, cx);
+    }
+
+    pub fn zoom(&self) -> f32 {
+        self.zoom
+    }
+
+    pub fn pan(&self) -> Point<Pixels> {
+        self.pan
+    }
+
+    // 用户手动修改视图（缩放/平移），之后窗口尺寸变化不再自动适配内容。会中断正在进行的视图动画
+    pub fn set_view(&mut self, zoom: f32, pan: Point<Pixels>, cx: &mut Context<Self>) {
+        self.transition_view(zoom, pan, None, false, cx);
+    }
+
+    // 以屏幕坐标 anchor 为中心缩放，保持该点下的世界坐标不变
+    pub fn zoom_at(&mut self, anchor: Point<Pixels>, zoom: f32, cx: &mut Context<Self>) {
+        let zoom = zoom.clamp(MIN_ZOOM, MAX_ZOOM);
+        let anchor_x = anchor.x.to_f64() as f32;
+        let anchor_y = anchor.y.to_f64() as f32;
+        let world_x = (anchor_x - self.pan.x.to_f64() as f32) / self.zoom;
+        let world_y = (anchor_y - self.pan.y.to_f64() as f32) / self.zoom;
+
+        let pan = point(px(anchor_x - world_x * zoom), px(anchor_y - world_y * zoom));
+        self.set_view(zoom, pan, cx);
+    }
+
+    /// 与 set_view 相同，但以动画过渡到目标视图
+    pub fn animate_view(&mut self, zoom: f32, pan: Point<Pixels>, cx: &mut Context<Self>) {
+        self.transition_view(zoom, pan, None, true, cx);
+    }
+
+    //****************************** 缩放命令 ****************************//
+    /// 放大到下一档预设比例，保持视口中心不变
+    pub fn zoom_in(&mut self, animate: bool, cx: &mut Context<Self>) {
+        let (zoom, _) = self.target_view();
+        let next = ZOOM_PRESETS.iter().copied().find(|preset| *preset > zoom * 1.001).unwrap_or(MAX_ZOOM);
+        self.zoom_about_view_center(next, animate, cx);
+    }
+
+    /// 缩小到上一档预设比例，保持视口中心不变
+    pub fn zoom_out(&mut self, animate: bool, cx: &mut Context<Self>) {
+        let (zoom, _) = self.target_view();
+        let previous = ZOOM_PRESETS.iter().rev().copied().find(|preset| *preset < zoom / 1.001).unwrap_or(MIN_ZOOM);
+        self.zoom_about_view_center(previous, animate, cx);
+    }
+
+    /// 缩放到 100%，保持视口中心不变
+    pub fn zoom_to_actual_size(&mut self, animate: bool, cx: &mut Context<Self>) {
+        self.zoom_about_view_center(1.0, animate, cx);
+    }
+
+    /// 缩放并平移使所有节点完整显示在视口内，没有节点时适配内容区域
+    pub fn zoom_to_fit(&mut self, animate: bool, cx: &mut Context<Self>) {
+        let (min_x, min_y, max_x, max_y) = self.content_bounds;
+        let nodes = self.nodes.clone();
+        let region = Self::union_geometry(&nodes, cx).unwrap_or((min_x, min_y, max_x - min_x, max_y - min_y));
+        self.fit_region(region, animate, cx);
+    }
+
+    /// 缩放并平移使选中节点完整显示在视口内，没有选中节点时不做任何操作
+    pub fn zoom_to_selection(&mut self, animate: bool, cx: &mut Context<Self>) {
+        let selected = self.get_selected_nodes(cx);
+        if let Some(region) = Self::union_geometry(&selected, cx) {
+            self.fit_region(region, animate, cx);
+        }
+    }
+
+    // 视图动画的目标视图，没有动画时为当前视图。连续执行缩放命令时以此为起点
+    fn target_view(&self) -> (f32, Point<Pixels>) {
+        match &self.view_animation {
+            Some(animation) => animation.to,
+            None => (self.zoom, self.pan),
+        }
+    }
+
+    fn zoom_about_view_center(&mut self, zoom: f32, animate: bool, cx: &mut Context<Self>) {
+        let (target_zoom, target_pan) = self.target_view();
+        let center = self.view_center_world(target_zoom, target_pan);
+        let zoom = zoom.clamp(MIN_ZOOM, MAX_ZOOM);
+        let pan = self.pan_centered_on(center, zoom);
+        self.transition_view(zoom, pan, Some((center.0, center.1, 0.0, 0.0)), animate, cx);
+    }
+
+    // 使 region（世界坐标）四周留出 FIT_PADDING 后居中显示在视口内
+    fn fit_region(&mut self, (x, y, width, height): RgGeometry, animate: bool, cx: &mut Context<Self>) {
+        let view_width = self.base_size.width.to_f64() as f32;
+        let view_height = self.base_size.height.to_f64() as f32;
+        if view_width <= 0.0 || view_height <= 0.0 {
+            return;
+        }
+
+        let available_width = (view_width - FIT_PADDING * 2.0).max(1.0);
+        let available_height = (view_height - FIT_PADDING * 2.0).max(1.0);
+        let zoom = (available_width / width.max(f32::EPSILON))
+            .min(available_height / height.max(f32::EPSILON))
+            .clamp(MIN_ZOOM, MAX_ZOOM);
+        let pan = self.pan_centered_on((x + width / 2.0, y + height / 2.0), zoom);
+        self.transition_view(zoom, pan, Some((x, y, width, height)), animate, cx);
+    }
+
+    // 切换到指定视图，target（世界坐标）为需要显示的区域，平移范围会扩展到包含它
+    fn transition_view(&mut self, zoom: f32, pan: Point<Pixels>, target: Option<RgGeometry>, animate: bool, cx: &mut Context<Self>) {
+        let zoom = zoom.clamp(MIN_ZOOM, MAX_ZOOM);
+        let pan = self.clamp_pan(pan, zoom, target);
+        self.user_zoomed = true;
+        self.view_initialized = true;
+
+        if animate {
+            self.view_animation = Some(RgViewAnimation {
+                start: Instant::now(),
+                from: (self.zoom, self.pan),
+                to: (zoom, pan),
+            });
+        } else {
+            self.view_animation = None;
+            self.zoom = zoom;
+            self.pan = pan;
+            self.update_nodes_pan_zoom(cx);
+        }
+        cx.refresh_windows();
+    }
+
+    // 视口中心对应的世界坐标
+    fn view_center_world(&self, zoom: f32, pan: Point<Pixels>) -> (f32, f32) {
+        (
+            (self.base_size.width.to_f64() as f32 / 2.0 - pan.x.to_f64() as f32) / zoom,
+            (self.base_size.height.to_f64() as f32 / 2.0 - pan.y.to_f64() as f32) / zoom,
+        )
+    }
+
+    // 使世界坐标 center 位于视口中心的平移
+    fn pan_centered_on(&self, (x, y): (f32, f32), zoom: f32) -> Point<Pixels> {
+        point(
+            self.base_size.width / 2.0 - px(x * zoom),
+            self.base_size.height / 2.0 - px(y * zoom),
+        )
+    }
+
+    // 按时间推进视图动画，返回动画是否仍在进行。节点的缩放和平移在渲染时同步
+    fn step_view_animation(&mut self) -> bool {
+        let Some(animation) = &self.view_animation else {
+            return false;
+        };
+
+        let t = (animation.start.elapsed().as_secs_f32() / VIEW_ANIMATION_DURATION.as_secs_f32()).min(1.0);
+        if t >= 1.0 {
+            (self.zoom, self.pan) = animation.to;
+            self.view_animation = None;
+            return false;
+        }
+
+        // 缓出
+        let eased = 1.0 - (1.0 - t).powi(3);
+        let (from_zoom, from_pan) = animation.from;
+        let (to_zoom, to_pan) = animation.to;
+        let from_center = self.view_center_world(from_zoom, from_pan);
+        let to_center = self.view_center_world(to_zoom, to_pan);
+
+        let zoom = from_zoom * (to_zoom / from_zoom).powf(eased);
+        let center = (
+            from_center.0 + (to_center.0 - from_center.0) * eased,
+            from_center.1 + (to_center.1 - from_center.1) * eased,
+        );
+        self.zoom = zoom;
+        self.pan = self.pan_centered_on(center, zoom);
+        true
+    }
+
+    // 限制平移范围，保证内容区域、所有节点和 target 的并集至少有 PAN_MARGIN 像素留在视口内
+    fn clamp_pan(&self, pan: Point<Pixels>, zoom: f32, target: Option<RgGeometry>) -> Point<Pixels> {
+        let view_width = self.base_size.width.to_f64() as f32;
+        let view_height = self.base_size.height.to_f64() as f32;
+        if view_width <= 0.0 || view_height <= 0.0 {
+            return pan;
+        }
+
+        let (min_x, min_y, max_x, max_y) = self.spatial_index.extent()
+            .into_iter()
+            .chain(target)
+            .fold(self.content_bounds, |(a, b, c, d), (x, y, width, height)| {
+                (a.min(x), b.min(y), c.max(x + width), d.max(y + height))
             });
+        let margin_x = PAN_MARGIN.min(view_width / 2.0);
+        let margin_y = PAN_MARGIN.min(view_height / 2.0);
+
+        let pan_x = (pan.x.to_f64() as f32).clamp(margin_x - max_x * zoom, view_width - margin_x - min_x * zoom);
+        let pan_y = (pan.y.to_f64() as f32).clamp(margin_y - max_y * zoom, view_height - margin_y - min_y * zoom);
+
+        point(px(pan_x), px(pan_y))
+    }
+
+    // 视口内需要渲染的节点（按 z 序），正在拖动的节点索引尚未更新，始终保留
+    fn get_visible_nodes(&self, cx: &App) -> Vec<Entity<RgRect>> {
+        if self.base_size.width <= px(0.0) || self.base_size.height <= px(0.0) {
+            return self.nodes.clone();
+        }
+
+        let margin = HIT_MARGIN * self.zoom.max(1.0) / self.zoom;
+        let (x, y, width, height) = self.visible_world_rect();
+        let mut ids: HashSet<u64> = self.spatial_index
+            .query_rect((x - margin, y - margin, width + margin * 2.0, height + margin * 2.0))
+            .into_iter()
+            .collect();
+
+        if let Some((dragged_nodes, _)) = &self.drag_state {
+            ids.extend(dragged_nodes.iter().map(|node_entity| node_entity.read(cx).id));
         }
+
+        let mut visible: Vec<(usize, Entity<RgRect>)> = ids.iter()
+            .filter_map(|id| self.node_lookup.get(id).cloned())
+            .collect();
+        visible.sort_by_key(|(index, _)| *index);
+
+        visible.into_iter().map(|(_, node_entity)| node_entity).collect()
     }
 
     fn get_selected_nodes(&self, cx: &mut Context<Self>) -> Vec<Entity<RgRect>> {
@@ -117,119 +834,1704 @@ impl RgEditor {
             .collect()
     }
 
-    fn is_node_in_selection_rect(&self, node_entity: &Entity<RgRect>, selection_rect: &(Point<Pixels>, Point<Pixels>), cx: &mut Context<Self>) -> bool {
+    // 框选矩形（屏幕坐标）内的节点，旋转的节点按实际形状检测
+    fn get_nodes_in_selection_rect(&self, selection_rect: &(Point<Pixels>, Point<Pixels>), cx: &App) -> Vec<u64> {
         let (start, end) = selection_rect;
+        let (left, top) = self.screen_to_world(point(start.x.min(end.x), start.y.min(end.y)));
+        let (right, bottom) = self.screen_to_world(point(start.x.max(end.x), start.y.max(end.y)));
+        let rect = (left, top, right - left, bottom - top);
 
-        cx.read_entity(node_entity, |node, _| {
-            let (node_screen_x, node_screen_y) = node.screen_position();
-            let (node_screen_width, node_screen_height) = node.screen_size();
+        self.spatial_index.query_rect(rect)
+            .into_iter()
+            .filter(|id| {
+                self.node_lookup.get(id).is_some_and(|(_, node_entity)| {
+                    let node = node_entity.read(cx);
+                    node.rotation == 0.0 || node.intersects_world_rect(rect)
+                })
+            })
+            .collect()
+    }
 
-            let rect_left = start.x.min(end.x);
-            let rect_right = start.x.max(end.x);
-            let rect_top = start.y.min(end.y);
-            let rect_bottom = start.y.max(end.y);
+    fn get_nodes_at_position_with_edges(&self, position: Point<Pixels>, cx: &mut Context<Self>) -> Vec<Entity<RgRect>> {
+        let mut nodes_at_position = Vec::new();
 
-            node_screen_x < rect_right.to_f64() as f32 &&
-            node_screen_x + node_screen_width > rect_left.to_f64() as f32 &&
-            node_screen_y < rect_bottom.to_f64() as f32 &&
-            node_screen_y + node_screen_height > rect_top.to_f64() as f32
-        })
+        // 先用空间索引筛选候选节点，再按 z 序从上到下精确检测
+        let (world_x, world_y) = self.screen_to_world(position);
+        let margin = HIT_MARGIN * self.zoom.max(1.0) / self.zoom;
+        let mut candidates: Vec<(usize, Entity<RgRect>)> = self.spatial_index
+            .query_point(world_x, world_y, margin)
+            .into_iter()
+            .filter_map(|id| self.node_lookup.get(&id).cloned())
+            .collect();
+        candidates.sort_by(|a, b| b.0.cmp(&a.0));
+
+        for (_, node_entity) in &candidates {
+            let is_hit = cx.read_entity(node_entity, |node, _| {
+                let handle = node.detect_handle_at(position);
+                handle != ResizeHandle::None
+            });
+
+            if is_hit {
+                nodes_at_position.push(node_entity.clone());
+            }
+        }
+
+        nodes_at_position
+    }
+
+    fn smart_select_nodes(&mut self, position: Point<Pixels>, cx: &mut Context<Self>) -> Option<Entity<RgRect>> {
+        let nodes_at_position = self.get_nodes_at_position_with_edges(position, cx);
+
+        if nodes_at_position.is_empty() {
+            return None;
+        }
+
+        let currently_selected = self.get_selected_nodes(cx);
+        let currently_selected_at_position: Vec<Entity<RgRect>> = currently_selected
+            .into_iter()
+            .filter(|node| nodes_at_position.contains(node))
+            .collect();
+
+        if !currently_selected_at_position.is_empty() {
+            let current_selected = &currently_selected_at_position[0];
+            let current_index = nodes_at_position.iter().position(|n| n == current_selected).unwrap();
+            let next_index = (current_index + 1) % nodes_at_position.len();
+            return Some(nodes_at_position[next_index].clone());
+        }
+
+        Some(nodes_at_position[0].clone())
+    }
+
+    fn bring_selected_nodes_to_front(&mut self, cx: &mut Context<Self>) {
+        let selected_nodes = self.get_selected_nodes(cx);
+
+        if selected_nodes.is_empty() {
+            return;
+        }
+
+        let mut new_order: Vec<Entity<RgRect>> = Vec::with_capacity(self.nodes.len());
+
+        for node in &self.nodes {
+            if !selected_nodes.contains(node) {
+                new_order.push(node.clone());
+            }
+        }
+
+        for node in selected_nodes {
+            new_order.push(node);
+        }
+
+        self.nodes = new_order;
+        self.rebuild_node_lookup(cx);
+    }
+
+    // 多选时隐藏单个节点的手柄，改为显示整体包围盒的手柄
+    fn show_select_handles(&mut self, cx: &mut Context<Self>) {
+        let selected_nodes = self.get_selected_nodes(cx);
+        for node_entity in &selected_nodes {
+            cx.update_entity(node_entity, |node, _| {
+                node.set_show_handles(selected_nodes.len() <= 1);
+            });
+        }
+    }
+
+    // 多选（两个及以上节点）时的整体包围盒（世界坐标）
+    fn selection_bounds(&self, cx: &mut Context<Self>) -> Option<RgGeometry> {
+        let selected_nodes = self.get_selected_nodes(cx);
+        if selected_nodes.len() < 2 {
+            return None;
+        }
+        Self::union_geometry(&selected_nodes, cx)
+    }
+
+    // 包围盒八个手柄的屏幕坐标
+    fn group_handle_positions(&self, bounds: RgGeometry) -> [(ResizeHandle, Point<Pixels>); 8] {
+        let (x, y, width, height) = bounds;
+        let left = self.pan.x + px(x * self.zoom);
+        let top = self.pan.y + px(y * self.zoom);
+        let right = left + px(width * self.zoom);
+        let bottom = top + px(height * self.zoom);
+        let center_x = (left + right) / 2.0;
+        let center_y = (top + bottom) / 2.0;
+
+        [
+            (ResizeHandle::TopLeft, point(left, top)),
+            (ResizeHandle::Top, point(center_x, top)),
+            (ResizeHandle::TopRight, point(right, top)),
+            (ResizeHandle::Right, point(right, center_y)),
+            (ResizeHandle::BottomRight, point(right, bottom)),
+            (ResizeHandle::Bottom, point(center_x, bottom)),
+            (ResizeHandle::BottomLeft, point(left, bottom)),
+            (ResizeHandle::Left, point(left, center_y)),
+        ]
+    }
+
+    fn detect_group_handle_at(&self, position: Point<Pixels>, bounds: RgGeometry) -> ResizeHandle {
+        let half_handle = px(GROUP_HANDLE_SIZE / 2.0 + 2.0);
+        self.group_handle_positions(bounds)
+            .into_iter()
+            .find(|(_, handle_position)| {
+                (position.x - handle_position.x).abs() <= half_handle
+                    && (position.y - handle_position.y).abs() <= half_handle
+            })
+            .map_or(ResizeHandle::None, |(handle, _)| handle)
+    }
+
+    fn clear_all_select(&mut self, cx: &mut Context<Self>) {
+        for node_entity in &self.nodes {
+            cx.update_entity(node_entity, |node, _| {
+                node.set_show_handles(true);
+            });
+        }
+
+        for node_entity in &self.nodes {
+            cx.update_entity(node_entity, |node, _| {
+                node.selected = false;
+            });
+        }
+        self.minimap_nodes = None;
+
+        self.clear_connector_selection();
+        self.key_node = None;
+    }
+
+    // 切换节点所在选择单元（节点或组）的选中状态
+    fn toggle_node_selection(&mut self, node_entity: &Entity<RgRect>, cx: &mut Context<Self>) {
+        let (id, selected) = cx.read_entity(node_entity, |node, _| (node.id, !node.selected));
+        for id in self.unit_node_ids(self.selection_unit(id)) {
+            if let Some(node_entity) = self.find_node(id) {
+                cx.update_entity(&node_entity, |node, _| {
+                    node.selected = selected;
+                });
+            }
+        }
+        self.minimap_nodes = None;
+    }
+
+    //****************************** 连线 ****************************//
+    /// 添加一条连线，两端节点都存在时返回新连线的 id（data.id 会被重新分配）
+    pub fn connect(&mut self, mut data: RgConnectorData, cx: &mut Context<Self>) -> Option<u64> {
+        if self.drag_state.is_some()
+            || data.source == data.target
+            || !self.node_lookup.contains_key(&data.source)
+            || !self.node_lookup.contains_key(&data.target)
+        {
+            return None;
+        }
+        self.commit_transaction(cx);
+
+        data.id = self.next_connector_id();
+        self.insert_connectors(std::slice::from_ref(&data));
+
+        let id = data.id;
+        let mut transaction = RgTransaction::new();
+        transaction.push(RgCommand::AddConnectors { connectors: vec![data] });
+        self.record_transaction(transaction, cx);
+        cx.refresh_windows();
+        Some(id)
+    }
+
+    pub fn connectors(&self) -> impl Iterator<Item = &RgConnectorData> {
+        self.connectors.iter().map(|connector| &connector.data)
+    }
+
+    fn next_connector_id(&self) -> u64 {
+        self.connectors.iter().map(|connector| connector.id() + 1).max().unwrap_or(0)
+    }
+
+    fn insert_connectors(&mut self, connectors: &[RgConnectorData]) {
+        self.connectors.extend(connectors.iter().cloned().map(RgConnector::new));
+    }
+
+    fn remove_connectors(&mut self, ids: &[u64]) -> Vec<RgConnectorData> {
+        for id in ids {
+            self.routed_connectors.remove(id);
+        }
+
+        let ids: HashSet<u64> = ids.iter().copied().collect();
+        let mut removed = Vec::new();
+        self.connectors.retain(|connector| {
+            let keep = !ids.contains(&connector.id());
+            if !keep {
+                removed.push(connector.data.clone());
+            }
+            keep
+        });
+        removed
+    }
+
+    fn clear_connector_selection(&mut self) {
+        for connector in &mut self.connectors {
+            connector.selected = false;
+        }
+    }
+
+    // 连线的折线（世界坐标），任意一端节点不存在时返回 None
+    fn connector_route(&self, connector: &RgConnector, cx: &App) -> Option<Vec<(f32, f32)>> {
+        let source = self.find_node(connector.data.source)?;
+        let target = self.find_node(connector.data.target)?;
+
+        if connector.data.route == RgConnectorRoute::Orthogonal {
+            if let Some(points) = self.routed_connectors.get(&connector.id()) {
+                return Some(points.clone());
+            }
+        }
+        Some(connector.route_points(source.read(cx), target.read(cx)))
+    }
+
+    // 为缓存中没有路径的正交连线计算绕开其他节点的路径
+    fn update_connector_routes(&mut self, cx: &App) {
+        let pending: Vec<usize> = self.connectors.iter()
+            .enumerate()
+            .filter(|(_, connector)| {
+                connector.data.route == RgConnectorRoute::Orthogonal && !self.routed_connectors.contains_key(&connector.id())
+            })
+            .map(|(index, _)| index)
+            .collect();
+        if pending.is_empty() {
+            return;
+        }
+
+        let dragged: Vec<Entity<RgRect>> = self.drag_state.as_ref()
+            .map(|(nodes, _)| nodes.clone())
+            .unwrap_or_default();
+
+        for index in pending {
+            let connector = &self.connectors[index];
+            let (Some(source), Some(target)) = (self.find_node(connector.data.source), self.find_node(connector.data.target)) else {
+                continue;
+            };
+            let (start, end) = connector.endpoints(source.read(cx), target.read(cx));
+
+            // 只考虑两端附近的节点，拖动中的节点在索引中还是旧位置，单独加入
+            let region = (
+                start.0.0.min(end.0.0) - ROUTE_SEARCH_MARGIN,
+                start.0.1.min(end.0.1) - ROUTE_SEARCH_MARGIN,
+                (start.0.0 - end.0.0).abs() + ROUTE_SEARCH_MARGIN * 2.0,
+                (start.0.1 - end.0.1).abs() + ROUTE_SEARCH_MARGIN * 2.0,
+            );
+            let mut obstacle_ids: HashSet<u64> = self.spatial_index.query_rect(region).into_iter().collect();
+            obstacle_ids.extend(dragged.iter().map(|node_entity| node_entity.read(cx).id));
+            obstacle_ids.extend([connector.data.source, connector.data.target]);
+            let obstacles: Vec<RgGeometry> = obstacle_ids.iter()
+                .filter_map(|id| self.find_node(*id))
+                .map(|node_entity| node_entity.read(cx).world_bounds())
+                .collect();
+
+            let others: Vec<Vec<(f32, f32)>> = self.routed_connectors.values().cloned().collect();
+            let points = self.router
+                .route(start, end, &obstacles, &others)
+                .unwrap_or_else(|| connector.route_points(source.read(cx), target.read(cx)));
+            self.routed_connectors.insert(connector.id(), points);
+        }
+    }
+
+    // 节点移动后清除连接到这些节点、或路径经过其新旧位置的连线缓存
+    fn invalidate_connector_routes_near(&mut self, nodes: &[Entity<RgRect>], cx: &App) {
+        if self.routed_connectors.is_empty() {
+            return;
+        }
+
+        let margin = self.router.margin;
+        let mut node_ids = HashSet::new();
+        let mut regions = Vec::new();
+        for node_entity in nodes {
+            let node = node_entity.read(cx);
+            node_ids.insert(node.id);
+            regions.push(node.world_bounds());
+            regions.extend(self.spatial_index.get(node.id));
+        }
+
+        let affected: Vec<u64> = self.connectors.iter()
+            .filter(|connector| {
+                node_ids.contains(&connector.data.source)
+                    || node_ids.contains(&connector.data.target)
+                    || self.routed_connectors.get(&connector.id()).is_some_and(|points| {
+                        // 正交路径的线段都是水平或竖直的，用线段包围盒判断是否经过节点附近
+                        points.windows(2).any(|segment| {
+                            let (a, b) = (segment[0], segment[1]);
+                            regions.iter().any(|(x, y, width, height)| {
+                                a.0.max(b.0) >= x - margin
+                                    && a.0.min(b.0) <= x + width + margin
+                                    && a.1.max(b.1) >= y - margin
+                                    && a.1.min(b.1) <= y + height + margin
+                            })
+                        })
+                    })
+            })
+            .map(|connector| connector.id())
+            .collect();
+
+        for id in affected {
+            self.routed_connectors.remove(&id);
+        }
+    }
+
+    // 位置（屏幕坐标）处最上层的连线
+    fn connector_at(&self, position: Point<Pixels>, cx: &App) -> Option<usize> {
+        let world_position = self.screen_to_world(position);
+        let tolerance = CONNECTOR_HIT_TOLERANCE / self.zoom;
+
+        self.connectors.iter()
+            .enumerate()
+            .rev()
+            .find(|(_, connector)| {
+                self.connector_route(connector, cx)
+                    .is_some_and(|points| RgConnector::hit_test(&points, world_position, tolerance))
+            })
+            .map(|(index, _)| index)
+    }
+
+    //****************************** 分组 ****************************//
+    // 节点在当前进入的组内对应的选择单元，不在该组内时取最外层的组
+    fn selection_unit(&self, id: u64) -> RgGroupMember {
+        self.groups.unit_for_node(id, self.entered_group)
+            .or_else(|| self.groups.unit_for_node(id, None))
+            .unwrap_or(RgGroupMember::Node(id))
+    }
+
+    // 选择单元包含的现存节点
+    fn unit_node_ids(&self, unit: RgGroupMember) -> Vec<u64> {
+        self.groups.leaf_nodes(unit)
+            .into_iter()
+            .filter(|id| self.node_lookup.contains_key(id))
+            .collect()
+    }
+
+    // 选中节点所属的选择单元，按首次出现的顺序
+    fn selected_units(&self, cx: &mut Context<Self>) -> Vec<RgGroupMember> {
+        let mut units = Vec::new();
+        let mut seen = HashSet::new();
+        for id in self.selected_node_ids(cx) {
+            let unit = self.selection_unit(id);
+            if seen.insert(unit) {
+                units.push(unit);
+            }
+        }
+        units
+    }
+
+    /// 将选中的节点和组合并为一个新组
+    pub fn group_selection(&mut self, cx: &mut Context<Self>) {
+        if self.drag_state.is_some() {
+            return;
+        }
+        self.commit_transaction(cx);
+
+        let units = self.selected_units(cx);
+        if units.len() < 2 {
+            return;
+        }
+
+        // 只合并同一父组中的成员，新组放在该父组中，避免把成员移出原来的组
+        let parent = self.groups.parent(units[0]);
+        if units.iter().any(|unit| self.groups.parent(*unit) != parent) {
+            return;
+        }
+        let group = RgGroupData {
+            id: self.groups.allocate_id(),
+            nodes: units.iter().filter_map(|unit| match unit {
+                RgGroupMember::Node(id) => Some(*id),
+                RgGroupMember::Group(_) => None,
+            }).collect(),
+            groups: units.iter().filter_map(|unit| match unit {
+                RgGroupMember::Group(id) => Some(*id),
+                RgGroupMember::Node(_) => None,
+            }).collect(),
+        };
+        let member_parents = self.groups.insert(group.clone(), parent);
+
+        let mut transaction = RgTransaction::new();
+        transaction.push(RgCommand::Group { group, parent, member_parents });
+        self.record_transaction(transaction, cx);
+        cx.refresh_windows();
+    }
+
+    /// 解散选中的组，成员移到组原来的父组中
+    pub fn ungroup_selection(&mut self, cx: &mut Context<Self>) {
+        if self.drag_state.is_some() {
+            return;
+        }
+        self.commit_transaction(cx);
+
+        let mut transaction = RgTransaction::new();
+        for unit in self.selected_units(cx) {
+            if let RgGroupMember::Group(id) = unit {
+                if let Some((group, parent)) = self.groups.remove(id) {
+                    transaction.push(RgCommand::Ungroup { group, parent });
+                }
+            }
+        }
+        self.validate_entered_group();
+
+        if !transaction.is_empty() {
+            self.record_transaction(transaction, cx);
+            cx.refresh_windows();
+        }
+    }
+
+    // 进入的组被解散后回到顶层
+    fn validate_entered_group(&mut self) {
+        if self.entered_group.is_some_and(|id| self.groups.get(id).is_none()) {
+            self.entered_group = None;
+        }
+    }
+
+    fn on_group_selection(&mut self, _: &GroupSelection, _window: &mut Window, cx: &mut Context<Self>) {
+        self.group_selection(cx);
+    }
+
+    fn on_align_left(&mut self, _: &AlignLeft, _window: &mut Window, cx: &mut Context<Self>) {
+        self.align_selection(RgAlignment::Left, cx);
+    }
+
+    fn on_align_horizontal_center(&mut self, _: &AlignHorizontalCenter, _window: &mut Window, cx: &mut Context<Self>) {
+        self.align_selection(RgAlignment::HorizontalCenter, cx);
+    }
+
+    fn on_align_right(&mut self, _: &AlignRight, _window: &mut Window, cx: &mut Context<Self>) {
+        self.align_selection(RgAlignment::Right, cx);
+    }
+
+    fn on_align_top(&mut self, _: &AlignTop, _window: &mut Window, cx: &mut Context<Self>) {
+        self.align_selection(RgAlignment::Top, cx);
+    }
+
+    fn on_align_vertical_center(&mut self, _: &AlignVerticalCenter, _window: &mut Window, cx: &mut Context<Self>) {
+        self.align_selection(RgAlignment::VerticalCenter, cx);
+    }
+
+    fn on_align_bottom(&mut self, _: &AlignBottom, _window: &mut Window, cx: &mut Context<Self>) {
+        self.align_selection(RgAlignment::Bottom, cx);
+    }
+
+    fn on_distribute_horizontal_spacing(&mut self, _: &DistributeHorizontalSpacing, _window: &mut Window, cx: &mut Context<Self>) {
+        self.distribute_selection(RgDistribution::HorizontalSpacing, cx);
+    }
+
+    fn on_distribute_vertical_spacing(&mut self, _: &DistributeVerticalSpacing, _window: &mut Window, cx: &mut Context<Self>) {
+        self.distribute_selection(RgDistribution::VerticalSpacing, cx);
+    }
+
+    fn on_distribute_horizontal_centers(&mut self, _: &DistributeHorizontalCenters, _window: &mut Window, cx: &mut Context<Self>) {
+        self.distribute_selection(RgDistribution::HorizontalCenters, cx);
+    }
+
+    fn on_distribute_vertical_centers(&mut self, _: &DistributeVerticalCenters, _window: &mut Window, cx: &mut Context<Self>) {
+        self.distribute_selection(RgDistribution::VerticalCenters, cx);
+    }
+
+    fn on_zoom_in(&mut self, _: &ZoomIn, _window: &mut Window, cx: &mut Context<Self>) {
+        self.zoom_in(true, cx);
+    }
+
+    fn on_zoom_out(&mut self, _: &ZoomOut, _window: &mut Window, cx: &mut Context<Self>) {
+        self.zoom_out(true, cx);
+    }
+
+    fn on_zoom_to_actual_size(&mut self, _: &ZoomToActualSize, _window: &mut Window, cx: &mut Context<Self>) {
+        self.zoom_to_actual_size(true, cx);
+    }
+
+    fn on_zoom_to_fit(&mut self, _: &ZoomToFit, _window: &mut Window, cx: &mut Context<Self>) {
+        self.zoom_to_fit(true, cx);
+    }
+
+    fn on_zoom_to_selection(&mut self, _: &ZoomToSelection, _window: &mut Window, cx: &mut Context<Self>) {
+        self.zoom_to_selection(true, cx);
+    }
+
+    fn on_ungroup_selection(&mut self, _: &UngroupSelection, _window: &mut Window, cx: &mut Context<Self>) {
+        self.ungroup_selection(cx);
+    }
+
+    //****************************** 样式 ****************************//
+    pub fn node_style(&self, id: u64, cx: &App) -> Option<RgStyle> {
+        self.find_node(id).map(|node_entity| node_entity.read(cx).style)
+    }
+
+    /// 修改指定节点的样式，作为一次操作记录到撤销栈
+    pub fn update_node_styles(&mut self, ids: &[u64], update: impl Fn(&mut RgStyle), cx: &mut Context<Self>) {
+        if self.drag_state.is_some() {
+            return;
+        }
+        self.commit_transaction(cx);
+
+        let mut changes = Vec::new();
+        for id in ids {
+            let Some(node_entity) = self.find_node(*id) else {
+                continue;
+            };
+            let change = cx.update_entity(&node_entity, |node, _| {
+                let before = node.style;
+                update(&mut node.style);
+                (before != node.style).then_some((node.id, before, node.style))
+            });
+            changes.extend(change);
+        }
+
+        if !changes.is_empty() {
+            let mut transaction = RgTransaction::new();
+            transaction.push(RgCommand::Restyle { changes });
+            self.record_transaction(transaction, cx);
+            cx.refresh_windows();
+        }
+    }
+
+    pub fn set_node_style(&mut self, ids: &[u64], style: RgStyle, cx: &mut Context<Self>) {
+        self.update_node_styles(ids, |node_style| *node_style = style, cx);
+    }
+
+    /// 修改所有选中节点的样式
+    pub fn update_selected_styles(&mut self, update: impl Fn(&mut RgStyle), cx: &mut Context<Self>) {
+        let ids = self.selected_node_ids(cx);
+        self.update_node_styles(&ids, update, cx);
+    }
+
+    //****************************** 标签 ****************************//
+    pub fn node_label(&self, id: u64, cx: &App) -> Option<String> {
+        self.find_node(id).map(|node_entity| node_entity.read(cx).label.clone())
+    }
+
+    /// 修改节点标签，作为一次操作记录到撤销栈
+    pub fn set_node_label(&mut self, id: u64, label: impl Into<String>, cx: &mut Context<Self>) {
+        if self.drag_state.is_some() {
+            return;
+        }
+        let Some(node_entity) = self.find_node(id) else {
+            return;
+        };
+        self.commit_transaction(cx);
+
+        let label = label.into();
+        let before = cx.update_entity(&node_entity, |node, _| std::mem::replace(&mut node.label, label.clone()));
+        if before != label {
+            let mut transaction = RgTransaction::new();
+            transaction.push(RgCommand::Relabel { changes: vec![(id, before, label)] });
+            self.record_transaction(transaction, cx);
+            cx.refresh_windows();
+        }
+    }
+
+    pub fn is_editing_label(&self) -> bool {
+        self.text_edit.is_some()
+    }
+
+    /// 在节点上打开原位编辑器，已有的编辑先提交。Enter 提交，Escape 取消
+    pub fn begin_label_edit(&mut self, id: u64, window: &mut Window, cx: &mut Context<Self>) {
+        self.commit_label_edit(window, cx);
+        let Some(node_entity) = self.find_node(id) else {
+            return;
+        };
+
+        let (label, style) = cx.update_entity(&node_entity, |node, _| {
+            node.is_editing_label = true;
+            (node.label.clone(), node.style)
+        });
+        let zoom = self.zoom;
+        let text_editor = cx.new(|cx| RgTextEditor::new(id, &label, style, zoom, cx));
+        let subscription = cx.subscribe_in(&text_editor, window, |this, _, event, window, cx| match event {
+            RgTextEditEvent::Commit => this.commit_label_edit(window, cx),
+            RgTextEditEvent::Cancel => this.cancel_label_edit(window, cx),
+        });
+
+        text_editor.read(cx).focus(window);
+        self.text_edit = Some((text_editor, subscription));
+        cx.refresh_windows();
+    }
+
+    /// 结束编辑，文字有变化时记录到撤销栈
+    pub fn commit_label_edit(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let Some((text_editor, _)) = self.text_edit.take() else {
+            return;
+        };
+        let (id, label, modified) = cx.read_entity(&text_editor, |text_editor, _| {
+            (text_editor.node_id, text_editor.content().to_string(), text_editor.is_modified())
+        });
+
+        self.end_label_edit(id, window, cx);
+        if modified {
+            self.set_node_label(id, label, cx);
+        }
+    }
+
+    /// 结束编辑并放弃修改
+    pub fn cancel_label_edit(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let Some((text_editor, _)) = self.text_edit.take() else {
+            return;
+        };
+        let id = text_editor.read(cx).node_id;
+        self.end_label_edit(id, window, cx);
+    }
+
+    fn end_label_edit(&mut self, id: u64, window: &mut Window, cx: &mut Context<Self>) {
+        if let Some(node_entity) = self.find_node(id) {
+            cx.update_entity(&node_entity, |node, _| {
+                node.is_editing_label = false;
+            });
+        }
+        window.focus(&self.focus_handle);
+        cx.refresh_windows();
+    }
+
+    //****************************** 自动布局 ****************************//
+    /// 对选中的节点（selected_only）或全部节点运行布局算法，组作为整体参与布局，连线作为布局的边。
+    /// 布局结果保持原包围盒的左上角不变，并作为一次移动记录到撤销栈
+    pub fn apply_layout(&mut self, layout: &dyn RgLayout, selected_only: bool, animate: bool, cx: &mut Context<Self>) {
+        if self.drag_state.is_some() {
+            return;
+        }
+        self.commit_transaction(cx);
+
+        let units = if selected_only {
+            self.selected_units(cx)
+        } else {
+            let mut units = Vec::new();
+            let mut seen = HashSet::new();
+            for id in self.node_ids(cx) {
+                let unit = self.groups.unit_for_node(id, None).unwrap_or(RgGroupMember::Node(id));
+                if seen.insert(unit) {
+                    units.push(unit);
+                }
+            }
+            units
+        };
+
+        let (unit_nodes, items) = self.unit_geometries(&units, cx);
+        if items.len() < 2 {
+            return;
+        }
+
+        let mut unit_of_node = HashMap::new();
+        for (index, nodes) in unit_nodes.iter().enumerate() {
+            for node_entity in nodes {
+                unit_of_node.insert(node_entity.read(cx).id, index);
+            }
+        }
+        let mut edges = Vec::new();
+        let mut seen_edges = HashSet::new();
+        for connector in &self.connectors {
+            if let (Some(a), Some(b)) = (unit_of_node.get(&connector.data.source), unit_of_node.get(&connector.data.target)) {
+                if a != b && seen_edges.insert((*a, *b)) {
+                    edges.push((*a, *b));
+                }
+            }
+        }
+
+        let positions = layout.layout(&items, &edges);
+        if positions.len() != items.len() {
+            return;
+        }
+
+        // 平移结果，使布局后的整体左上角与原来一致
+        let old_min = items.iter().fold((f32::INFINITY, f32::INFINITY), |min, item| (min.0.min(item.0), min.1.min(item.1)));
+        let new_min = positions.iter().fold((f32::INFINITY, f32::INFINITY), |min, p| (min.0.min(p.0), min.1.min(p.1)));
+        let offsets: Vec<(f32, f32)> = items.iter()
+            .zip(&positions)
+            .map(|(item, position)| (position.0 - new_min.0 + old_min.0 - item.0, position.1 - new_min.1 + old_min.1 - item.1))
+            .collect();
+
+        self.move_units(&unit_nodes, &offsets, animate, cx);
+    }
+
+    /// 将选中的单元对齐到关键对象，没有关键对象时对齐到选择包围盒
+    pub fn align_selection(&mut self, alignment: RgAlignment, cx: &mut Context<Self>) {
+        if self.drag_state.is_some() {
+            return;
+        }
+        self.commit_transaction(cx);
+
+        let units = self.selected_units(cx);
+        let (unit_nodes, items) = self.unit_geometries(&units, cx);
+        if items.len() < 2 {
+            return;
+        }
+
+        let key_unit = self.key_unit(cx);
+        let key_index = key_unit.and_then(|key| units.iter().position(|unit| *unit == key));
+        let reference = match key_index {
+            Some(index) => items[index],
+            None => {
+                let (min_x, min_y, max_x, max_y) = items.iter().fold(
+                    (f32::INFINITY, f32::INFINITY, f32::NEG_INFINITY, f32::NEG_INFINITY),
+                    |(min_x, min_y, max_x, max_y), (x, y, width, height)| {
+                        (min_x.min(*x), min_y.min(*y), max_x.max(x + width), max_y.max(y + height))
+                    },
+                );
+                (min_x, min_y, max_x - min_x, max_y - min_y)
+            }
+        };
+
+        let offsets = alignment.offsets(&items, reference);
+        self.move_units(&unit_nodes, &offsets, false, cx);
+    }
+
+    /// 在首尾两个单元之间均匀分布选中的单元
+    pub fn distribute_selection(&mut self, distribution: RgDistribution, cx: &mut Context<Self>) {
+        if self.drag_state.is_some() {
+            return;
+        }
+        self.commit_transaction(cx);
+
+        let units = self.selected_units(cx);
+        let (unit_nodes, items) = self.unit_geometries(&units, cx);
+        if items.len() < 3 {
+            return;
+        }
+
+        let offsets = distribution.offsets(&items);
+        self.move_units(&unit_nodes, &offsets, false, cx);
+    }
+
+    /// 对齐使用的关键对象（节点 id），其所在的组整体作为参照
+    pub fn key_object(&self) -> Option<u64> {
+        self.key_node
+    }
+
+    pub fn set_key_object(&mut self, node: Option<u64>, cx: &mut Context<Self>) {
+        self.key_node = node.filter(|id| self.node_lookup.contains_key(id));
+        cx.refresh_windows();
+    }
+
+    // 关键对象所在的选择单元，关键对象未被选中时返回 None
+    fn key_unit(&self, cx: &App) -> Option<RgGroupMember> {
+        let node_entity = self.find_node(self.key_node?)?;
+        let node = node_entity.read(cx);
+        node.selected.then(|| self.selection_unit(node.id))
+    }
+
+    // 每个单元的现存节点和世界坐标包围盒，没有节点的单元被跳过
+    fn unit_geometries(&self, units: &[RgGroupMember], cx: &mut Context<Self>) -> (Vec<Vec<Entity<RgRect>>>, Vec<RgGeometry>) {
+        let mut unit_nodes = Vec::new();
+        let mut items = Vec::new();
+
+        for unit in units {
+            let nodes: Vec<Entity<RgRect>> = self.unit_node_ids(*unit)
+                .into_iter()
+                .filter_map(|id| self.find_node(id))
+                .collect();
+            if let Some(bounds) = Self::union_geometry(&nodes, cx) {
+                unit_nodes.push(nodes);
+                items.push(bounds);
+            }
+        }
+
+        (unit_nodes, items)
+    }
+
+    // 按单元平移节点并记录为一次移动，animate 时以动画过渡到目标位置
+    fn move_units(&mut self, unit_nodes: &[Vec<Entity<RgRect>>], offsets: &[(f32, f32)], animate: bool, cx: &mut Context<Self>) {
+        let mut changes = Vec::new();
+        let mut animation_nodes = Vec::new();
+        for (nodes, (dx, dy)) in unit_nodes.iter().zip(offsets) {
+            if *dx == 0.0 && *dy == 0.0 {
+                continue;
+            }
+            for node_entity in nodes {
+                let node = node_entity.read(cx);
+                let before = (node.x, node.y, node.width, node.height);
+                let after = (node.x + dx, node.y + dy, node.width, node.height);
+                changes.push((node.id, before, after));
+                animation_nodes.push((node_entity.clone(), (before.0, before.1), (after.0, after.1)));
+            }
+        }
+        if changes.is_empty() {
+            return;
+        }
+
+        self.layout_animation = Some(RgLayoutAnimation {
+            start: Instant::now(),
+            nodes: animation_nodes,
+        });
+        if !animate {
+            self.finish_layout_animation(cx);
+        }
+
+        let mut transaction = RgTransaction::new();
+        transaction.push(RgCommand::Move { changes });
+        self.record_transaction(transaction, cx);
+        cx.refresh_windows();
+    }
+
+    // 按时间推进布局动画，返回动画是否仍在进行
+    fn step_layout_animation(&mut self, cx: &mut Context<Self>) -> bool {
+        let Some(animation) = &self.layout_animation else {
+            return false;
+        };
+
+        let t = (animation.start.elapsed().as_secs_f32() / LAYOUT_ANIMATION_DURATION.as_secs_f32()).min(1.0);
+        if t >= 1.0 {
+            self.finish_layout_animation(cx);
+            return false;
+        }
+
+        // 缓出
+        let eased = 1.0 - (1.0 - t).powi(3);
+        let nodes: Vec<Entity<RgRect>> = animation.nodes.iter().map(|(node_entity, _, _)| node_entity.clone()).collect();
+        for (node_entity, from, to) in &animation.nodes {
+            cx.update_entity(node_entity, |node, _| {
+                node.x = from.0 + (to.0 - from.0) * eased;
+                node.y = from.1 + (to.1 - from.1) * eased;
+            });
+        }
+        self.update_spatial_index(&nodes, cx);
+        self.invalidate_connector_routes_near(&nodes, cx);
+        true
+    }
+
+    // 立即把动画中的节点放到目标位置
+    fn finish_layout_animation(&mut self, cx: &mut Context<Self>) {
+        let Some(animation) = self.layout_animation.take() else {
+            return;
+        };
+
+        let nodes: Vec<Entity<RgRect>> = animation.nodes.iter().map(|(node_entity, _, _)| node_entity.clone()).collect();
+        for (node_entity, _, to) in &animation.nodes {
+            cx.update_entity(node_entity, |node, _| {
+                node.x = to.0;
+                node.y = to.1;
+            });
+        }
+        self.update_spatial_index(&nodes, cx);
+        self.routed_connectors.clear();
+        self.update_auto_content_bounds(cx);
+    }
+
+    //****************************** 撤销 / 重做 ****************************//
+    pub fn undo(&mut self, cx: &mut Context<Self>) {
+        if self.drag_state.is_some() {
+            return;
+        }
+        self.commit_transaction(cx);
+
+        if let Some(transaction) = self.history.pop_undo() {
+            for command in transaction.commands.iter().rev() {
+                self.apply_command(command, true, cx);
+            }
+            self.update_auto_content_bounds(cx);
+            self.emit_transaction_events(&transaction, true, cx);
+            self.show_select_handles(cx);
+            cx.refresh_windows();
+        }
+    }
+
+    pub fn redo(&mut self, cx: &mut Context<Self>) {
+        if self.drag_state.is_some() {
+            return;
+        }
+        self.commit_transaction(cx);
+
+        if let Some(transaction) = self.history.pop_redo() {
+            for command in &transaction.commands {
+                self.apply_command(command, false, cx);
+            }
+            self.update_auto_content_bounds(cx);
+            self.emit_transaction_events(&transaction, false, cx);
+            self.show_select_handles(cx);
+            cx.refresh_windows();
+        }
+    }
+
+    pub fn can_undo(&self) -> bool {
+        self.history.can_undo()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        self.history.can_redo()
+    }
+
+    fn on_undo(&mut self, _: &Undo, _window: &mut Window, cx: &mut Context<Self>) {
+        self.undo(cx);
+    }
+
+    fn on_redo(&mut self, _: &Redo, _window: &mut Window, cx: &mut Context<Self>) {
+        self.redo(cx);
+    }
+
+    fn node_ids(&self, cx: &mut Context<Self>) -> Vec<u64> {
+        self.nodes.iter()
+            .map(|node_entity| cx.read_entity(node_entity, |node, _| node.id))
+            .collect()
+    }
+
+    fn selected_node_ids(&self, cx: &mut Context<Self>) -> Vec<u64> {
+        self.nodes.iter()
+            .filter_map(|node_entity| {
+                cx.read_entity(node_entity, |node, _| node.selected.then_some(node.id))
+            })
+            .collect()
+    }
+
+    fn find_node(&self, id: u64) -> Option<Entity<RgRect>> {
+        self.node_lookup.get(&id).map(|(_, node_entity)| node_entity.clone())
+    }
+
+    // 记录操作开始时的选择与层级状态
+    fn begin_transaction(&mut self, cx: &mut Context<Self>) {
+        self.commit_transaction(cx);
+        self.transaction_start = Some((self.selected_node_ids(cx), self.node_ids(cx)));
+    }
+
+    // 记录拖动开始时节点的几何数据和角度
+    fn capture_drag_geometry(&mut self, nodes: &[Entity<RgRect>], cx: &mut Context<Self>) {
+        self.drag_start_geometry = nodes.iter()
+            .map(|node_entity| {
+                cx.read_entity(node_entity, |node, _| (node.id, (node.x, node.y, node.width, node.height), node.rotation))
+            })
+            .collect();
+    }
+
+    // 与开始状态比较，生成一条撤销记录
+    fn commit_transaction(&mut self, cx: &mut Context<Self>) {
+        self.finish_layout_animation(cx);
+
+        let Some((selection_before, order_before)) = self.transaction_start.take() else {
+            return;
+        };
+
+        let mut transaction = RgTransaction::new();
+
+        let selection_after = self.selected_node_ids(cx);
+        if selection_after != selection_before {
+            transaction.push(RgCommand::Select { before: selection_before, after: selection_after });
+        }
+
+        let order_after = self.node_ids(cx);
+        if order_after != order_before {
+            transaction.push(RgCommand::Reorder { before: order_before, after: order_after });
+        }
+
+        let mut moved = Vec::new();
+        let mut resized = Vec::new();
+        let mut rotated = Vec::new();
+        for (id, before, rotation_before) in std::mem::take(&mut self.drag_start_geometry) {
+            let Some(node_entity) = self.find_node(id) else {
+                continue;
+            };
+            let (after, rotation_after) = cx.read_entity(&node_entity, |node, _| {
+                ((node.x, node.y, node.width, node.height), node.rotation)
+            });
+            if rotation_after != rotation_before {
+                rotated.push((id, rotation_before, rotation_after));
+            }
+            if after == before {
+                continue;
+            }
+            if after.2 == before.2 && after.3 == before.3 {
+                moved.push((id, before, after));
+            } else {
+                resized.push((id, before, after));
+            }
+        }
+        if !moved.is_empty() {
+            transaction.push(RgCommand::Move { changes: moved });
+        }
+        if !resized.is_empty() {
+            transaction.push(RgCommand::Resize { changes: resized });
+        }
+        if !rotated.is_empty() {
+            transaction.push(RgCommand::Rotate { changes: rotated });
+        }
+
+        self.record_transaction(transaction, cx);
+    }
+
+    // 发出事件并记录到撤销栈
+    fn record_transaction(&mut self, transaction: RgTransaction, cx: &mut Context<Self>) {
+        self.emit_transaction_events(&transaction, false, cx);
+        self.history.push(transaction);
+        self.update_auto_content_bounds(cx);
+    }
+
+    // 根据命令发出事件，撤销时交换新旧数据并按相反顺序发出
+    fn emit_transaction_events(&self, transaction: &RgTransaction, undo: bool, cx: &mut Context<Self>) {
+        let swap = |changes: &Vec<(u64, RgGeometry, RgGeometry)>| {
+            changes.iter()
+                .map(|(id, before, after)| if undo { (*id, *after, *before) } else { (*id, *before, *after) })
+                .collect::<Vec<_>>()
+        };
+
+        let mut events: Vec<RgEditorEvent> = transaction.commands.iter()
+            .map(|command| match command {
+                RgCommand::Move { changes } => RgEditorEvent::NodesMoved { changes: swap(changes) },
+                RgCommand::Resize { changes } => RgEditorEvent::NodesResized { changes: swap(changes) },
+                RgCommand::Rotate { changes } => RgEditorEvent::NodesRotated {
+                    changes: changes.iter()
+                        .map(|(id, before, after)| if undo { (*id, *after, *before) } else { (*id, *before, *after) })
+                        .collect(),
+                },
+                RgCommand::Restyle { changes } => RgEditorEvent::NodesRestyled {
+                    changes: changes.iter()
+                        .map(|(id, before, after)| if undo { (*id, *after, *before) } else { (*id, *before, *after) })
+                        .collect(),
+                },
+                RgCommand::Relabel { changes } => RgEditorEvent::NodesRelabeled {
+                    changes: changes.iter()
+                        .map(|(id, before, after)| if undo { (*id, after.clone(), before.clone()) } else { (*id, before.clone(), after.clone()) })
+                        .collect(),
+                },
+                RgCommand::Reorder { before, after } => {
+                    let (before, after) = if undo { (after, before) } else { (before, after) };
+                    RgEditorEvent::NodesReordered { before: before.clone(), after: after.clone() }
+                }
+                RgCommand::Select { before, after } => {
+                    let (before, after) = if undo { (after, before) } else { (before, after) };
+                    RgEditorEvent::SelectionChanged { before: before.clone(), after: after.clone() }
+                }
+                RgCommand::Add { nodes } if undo => RgEditorEvent::NodesRemoved { nodes: nodes.clone() },
+                RgCommand::Add { nodes } => RgEditorEvent::NodesAdded { nodes: nodes.clone() },
+                RgCommand::Remove { nodes } if undo => RgEditorEvent::NodesAdded { nodes: nodes.clone() },
+                RgCommand::Remove { nodes } => RgEditorEvent::NodesRemoved { nodes: nodes.clone() },
+                RgCommand::Group { group, .. } if undo => RgEditorEvent::NodesUngrouped { group: group.clone() },
+                RgCommand::Group { group, .. } => RgEditorEvent::NodesGrouped { group: group.clone() },
+                RgCommand::Ungroup { group, .. } if undo => RgEditorEvent::NodesGrouped { group: group.clone() },
+                RgCommand::Ungroup { group, .. } => RgEditorEvent::NodesUngrouped { group: group.clone() },
+                RgCommand::AddConnectors { connectors } if undo => RgEditorEvent::ConnectorsRemoved { connectors: connectors.clone() },
+                RgCommand::AddConnectors { connectors } => RgEditorEvent::ConnectorsAdded { connectors: connectors.clone() },
+                RgCommand::RemoveConnectors { connectors } if undo => RgEditorEvent::ConnectorsAdded { connectors: connectors.clone() },
+                RgCommand::RemoveConnectors { connectors } => RgEditorEvent::ConnectorsRemoved { connectors: connectors.clone() },
+            })
+            .collect();
+
+        if undo {
+            events.reverse();
+        }
+        for event in events {
+            cx.emit(event);
+        }
+    }
+
+    fn apply_command(&mut self, command: &RgCommand, undo: bool, cx: &mut Context<Self>) {
+        self.routed_connectors.clear();
+        self.minimap_nodes = None;
+
+        match command {
+            RgCommand::Move { changes } | RgCommand::Resize { changes } => {
+                for (id, before, after) in changes {
+                    let (x, y, width, height) = if undo { *before } else { *after };
+                    if let Some(node_entity) = self.find_node(*id) {
+                        let bounds = cx.update_entity(&node_entity, |node, _| {
+                            node.x = x;
+                            node.y = y;
+                            node.width = width;
+                            node.height = height;
+                            node.world_bounds()
+                        });
+                        self.spatial_index.insert(*id, bounds);
+                    }
+                }
+            }
+
+            RgCommand::Rotate { changes } => {
+                for (id, before, after) in changes {
+                    let rotation = if undo { *before } else { *after };
+                    if let Some(node_entity) = self.find_node(*id) {
+                        let bounds = cx.update_entity(&node_entity, |node, _| {
+                            node.rotation = rotation;
+                            node.world_bounds()
+                        });
+                        self.spatial_index.insert(*id, bounds);
+                    }
+                }
+            }
+
+            RgCommand::Restyle { changes } => {
+                for (id, before, after) in changes {
+                    let style = if undo { *before } else { *after };
+                    if let Some(node_entity) = self.find_node(*id) {
+                        cx.update_entity(&node_entity, |node, _| {
+                            node.style = style;
+                        });
+                    }
+                }
+            }
+
+            RgCommand::Relabel { changes } => {
+                for (id, before, after) in changes {
+                    let label = if undo { before } else { after };
+                    if let Some(node_entity) = self.find_node(*id) {
+                        cx.update_entity(&node_entity, |node, _| {
+                            node.label = label.clone();
+                        });
+                    }
+                }
+            }
+
+            RgCommand::Reorder { before, after } => {
+                let order = if undo { before } else { after };
+                self.reorder_nodes(order, cx);
+            }
+
+            RgCommand::Select { before, after } => {
+                let selection = if undo { before } else { after };
+                self.set_selection(selection, cx);
+            }
+
+            RgCommand::Add { nodes } => {
+                if undo {
+                    self.remove_nodes(&nodes.iter().map(|n| n.id).collect::<Vec<_>>(), cx);
+                } else {
+                    self.insert_nodes(nodes, cx);
+                }
+            }
+
+            RgCommand::Remove { nodes } => {
+                if undo {
+                    self.insert_nodes(nodes, cx);
+                } else {
+                    self.remove_nodes(&nodes.iter().map(|n| n.id).collect::<Vec<_>>(), cx);
+                }
+            }
+
+            RgCommand::Group { group, parent, member_parents } => {
+                if undo {
+                    self.groups.remove(group.id);
+                    for (member, member_parent) in member_parents {
+                        self.groups.move_member(*member, *member_parent);
+                    }
+                    self.validate_entered_group();
+                } else {
+                    self.groups.insert(group.clone(), *parent);
+                }
+            }
+
+            RgCommand::Ungroup { group, parent } => {
+                if undo {
+                    self.groups.insert(group.clone(), *parent);
+                } else {
+                    self.groups.remove(group.id);
+                    self.validate_entered_group();
+                }
+            }
+
+            RgCommand::AddConnectors { connectors } => {
+                if undo {
+                    self.remove_connectors(&connectors.iter().map(|c| c.id).collect::<Vec<_>>());
+                } else {
+                    self.insert_connectors(connectors);
+                }
+            }
+
+            RgCommand::RemoveConnectors { connectors } => {
+                if undo {
+                    self.insert_connectors(connectors);
+                } else {
+                    self.remove_connectors(&connectors.iter().map(|c| c.id).collect::<Vec<_>>());
+                }
+            }
+        }
+    }
+
+    fn set_selection(&mut self, ids: &[u64], cx: &mut Context<Self>) {
+        let ids: HashSet<u64> = ids.iter().copied().collect();
+        for node_entity in &self.nodes {
+            cx.update_entity(node_entity, |node, _| {
+                node.selected = ids.contains(&node.id);
+            });
+        }
+        self.minimap_nodes = None;
+    }
+
+    // 按快照中的 z_order 从小到大插回节点
+    fn insert_nodes(&mut self, nodes: &[RgNodeData], cx: &mut Context<Self>) {
+        let mut nodes = nodes.to_vec();
+        nodes.sort_by_key(|node| node.z_order);
+
+        let zoom = self.zoom;
+        let pan = (self.pan.x.to_f64() as f32, self.pan.y.to_f64() as f32);
+
+        for data in nodes {
+            let index = data.z_order.min(self.nodes.len());
+            let node_entity = cx.new(|_| {
+                let mut node = RgRect::from_node_data(&data);
+                node.zoom = zoom;
+                node.pan = pan;
+                node
+            });
+            self.nodes.insert(index, node_entity);
+        }
+
+        self.rebuild_node_index(cx);
+    }
+
+    // 移除节点并返回快照，z_order 记录其原下标
+    fn remove_nodes(&mut self, ids: &[u64], cx: &mut Context<Self>) -> Vec<RgNodeData> {
+        let ids: HashSet<u64> = ids.iter().copied().collect();
+        let mut removed = Vec::new();
+        let mut kept = Vec::with_capacity(self.nodes.len());
+
+        for (index, node_entity) in std::mem::take(&mut self.nodes).into_iter().enumerate() {
+            let data = cx.read_entity(&node_entity, |node, _| {
+                ids.contains(&node.id).then(|| node.to_node_data(index))
+            });
+            match data {
+                Some(data) => removed.push(data),
+                None => kept.push(node_entity),
+            }
+        }
+
+        self.nodes = kept;
+        for data in &removed {
+            self.spatial_index.remove(data.id);
+        }
+        self.routed_connectors.clear();
+        self.rebuild_node_lookup(cx);
+        removed
+    }
+
+    //****************************** 键盘操作 ****************************//
+    pub fn nudge_selected_nodes(&mut self, dx: f32, dy: f32, cx: &mut Context<Self>) {
+        if self.drag_state.is_some() {
+            return;
+        }
+        self.commit_transaction(cx);
+
+        let selected_nodes = self.get_selected_nodes(cx);
+        let (dx, dy) = match Self::union_geometry(&selected_nodes, cx) {
+            Some((x, y, width, height)) if self.content_bounds_mode == RgContentBoundsMode::Artboard => {
+                let (offset_x, offset_y) = Self::artboard_offset(self.content_bounds, (x + dx, y + dy, width, height));
+                (dx + offset_x, dy + offset_y)
+            }
+            _ => (dx, dy),
+        };
+
+        let mut changes = Vec::new();
+        for node_entity in selected_nodes {
+            let (change, bounds) = cx.update_entity(&node_entity, |node, _| {
+                let before = (node.x, node.y, node.width, node.height);
+                node.x += dx;
+                node.y += dy;
+                ((node.id, before, (node.x, node.y, node.width, node.height)), node.world_bounds())
+            });
+            self.spatial_index.insert(change.0, bounds);
+            changes.push(change);
+        }
+
+        if !changes.is_empty() {
+            self.routed_connectors.clear();
+            self.minimap_nodes = None;
+            let mut transaction = RgTransaction::new();
+            transaction.push(RgCommand::Move { changes });
+            self.record_transaction(transaction, cx);
+            cx.refresh_windows();
+        }
+    }
+
+    pub fn delete_selected_nodes(&mut self, cx: &mut Context<Self>) {
+        if self.drag_state.is_some() {
+            return;
+        }
+        self.commit_transaction(cx);
+
+        // 删除节点时一并删除连接到这些节点的连线
+        let selected = self.selected_node_ids(cx);
+        let connector_ids: Vec<u64> = self.connectors.iter()
+            .filter(|connector| connector.selected || selected.iter().any(|id| connector.is_attached_to(*id)))
+            .map(|connector| connector.id())
+            .collect();
+        if selected.is_empty() && connector_ids.is_empty() {
+            return;
+        }
+
+        let removed_connectors = self.remove_connectors(&connector_ids);
+        let removed = self.remove_nodes(&selected, cx);
+
+        let mut transaction = RgTransaction::new();
+        if !selected.is_empty() {
+            transaction.push(RgCommand::Select { before: selected, after: Vec::new() });
+        }
+        if !removed_connectors.is_empty() {
+            transaction.push(RgCommand::RemoveConnectors { connectors: removed_connectors });
+        }
+        if !removed.is_empty() {
+            transaction.push(RgCommand::Remove { nodes: removed });
+        }
+        self.record_transaction(transaction, cx);
+        cx.refresh_windows();
+    }
+
+    pub fn select_all(&mut self, cx: &mut Context<Self>) {
+        if self.drag_state.is_some() {
+            return;
+        }
+        self.begin_transaction(cx);
+        let ids = self.node_ids(cx);
+        self.set_selection(&ids, cx);
+        self.show_select_handles(cx);
+        self.commit_transaction(cx);
+        cx.refresh_windows();
+    }
+
+    pub fn clear_selection(&mut self, cx: &mut Context<Self>) {
+        if self.drag_state.is_some() {
+            return;
+        }
+        self.begin_transaction(cx);
+        self.clear_all_select(cx);
+        self.entered_group = None;
+        self.commit_transaction(cx);
+        cx.refresh_windows();
+    }
+
+    // 按 z 序循环切换选中节点，reverse 为 true 时反向
+    pub fn select_next_node(&mut self, reverse: bool, cx: &mut Context<Self>) {
+        if self.drag_state.is_some() || self.nodes.is_empty() {
+            return;
+        }
+
+        let count = self.nodes.len();
+        let current = if reverse {
+            self.nodes.iter().position(|n| cx.read_entity(n, |node, _| node.selected))
+        } else {
+            self.nodes.iter().rposition(|n| cx.read_entity(n, |node, _| node.selected))
+        };
+        let next_index = match (current, reverse) {
+            (Some(index), false) => (index + 1) % count,
+            (Some(index), true) => (index + count - 1) % count,
+            (None, false) => 0,
+            (None, true) => count - 1,
+        };
+        let next_id = cx.read_entity(&self.nodes[next_index], |node, _| node.id);
+
+        self.begin_transaction(cx);
+        self.set_selection(&[next_id], cx);
+        self.show_select_handles(cx);
+        self.commit_transaction(cx);
+        cx.refresh_windows();
+    }
+
+    //****************************** 剪贴板 ****************************//
+    // 选中节点的快照，z_order 为其在选中集合中的相对顺序
+    fn selected_node_data(&self, cx: &mut Context<Self>) -> Vec<RgNodeData> {
+        self.get_selected_nodes(cx)
+            .iter()
+            .enumerate()
+            .map(|(z_order, node_entity)| cx.read_entity(node_entity, |node, _| node.to_node_data(z_order)))
+            .collect()
+    }
+
+    // 成员全部被选中的组
+    fn selected_group_data(&self, cx: &mut Context<Self>) -> Vec<RgGroupData> {
+        let selected: HashSet<u64> = self.selected_node_ids(cx).into_iter().collect();
+        self.groups.to_data(|id| selected.contains(&id))
+            .into_iter()
+            .filter(|group| {
+                self.unit_node_ids(RgGroupMember::Group(group.id))
+                    .iter()
+                    .all(|id| selected.contains(id))
+            })
+            .collect()
+    }
+
+    // 两端节点都被选中的连线
+    fn selected_connector_data(&self, cx: &mut Context<Self>) -> Vec<RgConnectorData> {
+        let selected: HashSet<u64> = self.selected_node_ids(cx).into_iter().collect();
+        self.connectors.iter()
+            .filter(|connector| selected.contains(&connector.data.source) && selected.contains(&connector.data.target))
+            .map(|connector| connector.data.clone())
+            .collect()
+    }
+
+    /// 复制选中节点到系统剪贴板：元数据中保存结构化数据，文本为同一数据的 JSON。
+    /// 没有选中节点或写入失败时返回 false
+    pub fn copy_selection(&mut self, cx: &mut Context<Self>) -> bool {
+        let nodes = self.selected_node_data(cx);
+        if nodes.is_empty() {
+            return false;
+        }
+
+        let data = RgClipboardData::new(nodes, self.selected_group_data(cx), self.selected_connector_data(cx));
+        let Ok(text) = data.to_json() else {
+            return false;
+        };
+        cx.write_to_clipboard(ClipboardItem::new_string_with_json_metadata(text, data));
+        self.paste_count = 0;
+        true
+    }
+
+    // 只选中连线时剪贴板中没有内容可以粘贴回来，不删除
+    pub fn cut_selection(&mut self, cx: &mut Context<Self>) {
+        if self.drag_state.is_some() || !self.copy_selection(cx) {
+            return;
+        }
+        self.delete_selected_nodes(cx);
+        // 剪切后第一次粘贴放回原位置
+        self.paste_count = 0;
+    }
+
+    /// 粘贴剪贴板中的节点，at_cursor 为 true 时以鼠标位置为中心，否则相对原位置偏移
+    pub fn paste(&mut self, at_cursor: bool, cx: &mut Context<Self>) {
+        let Some(data) = cx.read_from_clipboard().and_then(|item| Self::clipboard_data(&item)) else {
+            return;
+        };
+
+        if at_cursor {
+            if let Some(position) = self.last_mouse_position {
+                self.paste_nodes_at(data.nodes, data.groups, data.connectors, position, cx);
+                return;
+            }
+        }
+
+        self.paste_count += 1;
+        let offset = PASTE_OFFSET * self.paste_count as f32;
+        self.paste_nodes(data.nodes, data.groups, data.connectors, (offset, offset), cx);
+    }
+
+    /// 添加图片节点到最上层并选中，作为一次可撤销的操作，返回节点 id
+    pub fn add_image(&mut self, image: RgImage, geometry: RgGeometry, cx: &mut Context<Self>) -> Option<u64> {
+        self.add_images(vec![(image, geometry)], cx).into_iter().next()
+    }
+
+    // 多个图片节点作为一次操作添加
+    fn add_images(&mut self, images: Vec<(RgImage, RgGeometry)>, cx: &mut Context<Self>) -> Vec<u64> {
+        if self.drag_state.is_some() || images.is_empty() {
+            return Vec::new();
+        }
+
+        let nodes: Vec<RgNodeData> = images.into_iter()
+            .enumerate()
+            .map(|(index, (image, (x, y, width, height)))| {
+                RgRect::new_image(index as u64, x, y, width.max(MIN_NODE_SIZE), height.max(MIN_NODE_SIZE), image)
+                    .to_node_data(index)
+            })
+            .collect();
+        self.paste_nodes(nodes, Vec::new(), Vec::new(), (0.0, 0.0), cx)
+    }
+
+    /// 原地复制选中节点（不经过剪贴板）
+    pub fn duplicate_selection(&mut self, cx: &mut Context<Self>) {
+        let nodes = self.selected_node_data(cx);
+        if nodes.is_empty() {
+            return;
+        }
+        let groups = self.selected_group_data(cx);
+        let connectors = self.selected_connector_data(cx);
+        self.paste_nodes(nodes, groups, connectors, (PASTE_OFFSET, PASTE_OFFSET), cx);
+    }
+
+    fn clipboard_data(item: &ClipboardItem) -> Option<RgClipboardData> {
+        item.entries().iter().find_map(|entry| match entry {
+            ClipboardEntry::String(string) => string
+                .metadata_json::<RgClipboardData>()
+                .or_else(|| RgClipboardData::from_json(string.text())),
+            _ => None,
+        })
+    }
+
+    fn paste_nodes_at(
+        &mut self,
+        nodes: Vec<RgNodeData>,
+        groups: Vec<RgGroupData>,
+        connectors: Vec<RgConnectorData>,
+        position: Point<Pixels>,
+        cx: &mut Context<Self>,
+    ) {
+        let Some((min_x, min_y, max_x, max_y)) = nodes.iter().fold(None, |bounds: Option<(f32, f32, f32, f32)>, node| {
+            let (x0, y0, x1, y1) = (node.x, node.y, node.x + node.width, node.y + node.height);
+            Some(match bounds {
+                Some((a, b, c, d)) => (a.min(x0), b.min(y0), c.max(x1), d.max(y1)),
+                None => (x0, y0, x1, y1),
+            })
+        }) else {
+            return;
+        };
+
+        let (world_x, world_y) = self.screen_to_world(position);
+        let offset = (world_x - (min_x + max_x) / 2.0, world_y - (min_y + max_y) / 2.0);
+        self.paste_nodes(nodes, groups, connectors, offset, cx);
+    }
+
+    // 以新 id 插入到最上层并选中，同时按新 id 重建分组和连线，作为一次可撤销的操作。返回按 z 序排列的新 id
+    fn paste_nodes(
+        &mut self,
+        mut nodes: Vec<RgNodeData>,
+        groups: Vec<RgGroupData>,
+        connectors: Vec<RgConnectorData>,
+        offset: (f32, f32),
+        cx: &mut Context<Self>,
+    ) -> Vec<u64> {
+        if self.drag_state.is_some() || nodes.is_empty() {
+            return Vec::new();
+        }
+        self.commit_transaction(cx);
+
+        let selection_before = self.selected_node_ids(cx);
+        let mut next_id = self.node_lookup.keys().max().map_or(0, |id| id + 1);
+        let base_z_order = self.nodes.len();
+
+        nodes.sort_by_key(|node| node.z_order);
+        let mut node_ids = HashMap::new();
+        for (index, node) in nodes.iter_mut().enumerate() {
+            node_ids.insert(node.id, next_id);
+            node.id = next_id;
+            node.x += offset.0;
+            node.y += offset.1;
+            node.z_order = base_z_order + index;
+            next_id += 1;
+        }
+        let pasted_ids: Vec<u64> = nodes.iter().map(|node| node.id).collect();
+
+        self.insert_nodes(&nodes, cx);
+        self.set_selection(&pasted_ids, cx);
+        self.show_select_handles(cx);
+
+        let mut transaction = RgTransaction::new();
+        transaction.push(RgCommand::Add { nodes });
+
+        let mut next_connector_id = self.next_connector_id();
+        let connectors: Vec<RgConnectorData> = connectors.into_iter()
+            .filter_map(|mut connector| {
+                connector.source = *node_ids.get(&connector.source)?;
+                connector.target = *node_ids.get(&connector.target)?;
+                connector.id = next_connector_id;
+                next_connector_id += 1;
+                Some(connector)
+            })
+            .collect();
+        if !connectors.is_empty() {
+            self.insert_connectors(&connectors);
+            transaction.push(RgCommand::AddConnectors { connectors });
+        }
+
+        // 剪贴板中子组排在父组之前，按顺序创建即可恢复层级
+        let mut group_ids = HashMap::new();
+        for group in groups {
+            let old_id = group.id;
+            let group = RgGroupData {
+                id: self.groups.allocate_id(),
+                nodes: group.nodes.iter().filter_map(|id| node_ids.get(id).copied()).collect(),
+                groups: group.groups.iter().filter_map(|id| group_ids.get(id).copied()).collect(),
+            };
+            if group.nodes.is_empty() && group.groups.is_empty() {
+                continue;
+            }
+            group_ids.insert(old_id, group.id);
+            let member_parents = self.groups.insert(group.clone(), None);
+            transaction.push(RgCommand::Group { group, parent: None, member_parents });
+        }
+
+        transaction.push(RgCommand::Select { before: selection_before, after: pasted_ids.clone() });
+        self.record_transaction(transaction, cx);
+        cx.refresh_windows();
+        pasted_ids
+    }
+
+    fn on_copy(&mut self, _: &CopyNodes, _window: &mut Window, cx: &mut Context<Self>) {
+        self.copy_selection(cx);
+    }
+
+    fn on_cut(&mut self, _: &CutNodes, _window: &mut Window, cx: &mut Context<Self>) {
+        self.cut_selection(cx);
+    }
+
+    fn on_paste(&mut self, _: &PasteNodes, _window: &mut Window, cx: &mut Context<Self>) {
+        self.paste(false, cx);
+    }
+
+    fn on_paste_at_cursor(&mut self, _: &PasteNodesAtCursor, _window: &mut Window, cx: &mut Context<Self>) {
+        self.paste(true, cx);
+    }
+
+    fn on_duplicate(&mut self, _: &DuplicateNodes, _window: &mut Window, cx: &mut Context<Self>) {
+        self.duplicate_selection(cx);
+    }
+
+    fn on_nudge_left(&mut self, _: &NudgeLeft, _window: &mut Window, cx: &mut Context<Self>) {
+        self.nudge_selected_nodes(-NUDGE_STEP, 0.0, cx);
+    }
+
+    fn on_nudge_right(&mut self, _: &NudgeRight, _window: &mut Window, cx: &mut Context<Self>) {
+        self.nudge_selected_nodes(NUDGE_STEP, 0.0, cx);
     }
 
-    fn get_nodes_at_position_with_edges(&self, position: Point<Pixels>, cx: &mut Context<Self>) -> Vec<Entity<RgRect>> {
-        let mut nodes_at_position = Vec::new();
+    fn on_nudge_up(&mut self, _: &NudgeUp, _window: &mut Window, cx: &mut Context<Self>) {
+        self.nudge_selected_nodes(0.0, -NUDGE_STEP, cx);
+    }
 
-        for node_entity in self.nodes.iter().rev() {
-            let is_hit = cx.read_entity(node_entity, |node, _| {
-                let handle = node.detect_handle_at(position);
-                handle != ResizeHandle::None
-            });
+    fn on_nudge_down(&mut self, _: &NudgeDown, _window: &mut Window, cx: &mut Context<Self>) {
+        self.nudge_selected_nodes(0.0, NUDGE_STEP, cx);
+    }
 
-            if is_hit {
-                nodes_at_position.push(node_entity.clone());
-            }
-        }
+    fn on_nudge_left_large(&mut self, _: &NudgeLeftLarge, _window: &mut Window, cx: &mut Context<Self>) {
+        self.nudge_selected_nodes(-NUDGE_STEP_LARGE, 0.0, cx);
+    }
 
-        nodes_at_position
+    fn on_nudge_right_large(&mut self, _: &NudgeRightLarge, _window: &mut Window, cx: &mut Context<Self>) {
+        self.nudge_selected_nodes(NUDGE_STEP_LARGE, 0.0, cx);
     }
 
-    fn smart_select_nodes(&mut self, position: Point<Pixels>, cx: &mut Context<Self>) -> Option<Entity<RgRect>> {
-        let nodes_at_position = self.get_nodes_at_position_with_edges(position, cx);
+    fn on_nudge_up_large(&mut self, _: &NudgeUpLarge, _window: &mut Window, cx: &mut Context<Self>) {
+        self.nudge_selected_nodes(0.0, -NUDGE_STEP_LARGE, cx);
+    }
 
-        if nodes_at_position.is_empty() {
-            return None;
-        }
+    fn on_nudge_down_large(&mut self, _: &NudgeDownLarge, _window: &mut Window, cx: &mut Context<Self>) {
+        self.nudge_selected_nodes(0.0, NUDGE_STEP_LARGE, cx);
+    }
 
-        let currently_selected = self.get_selected_nodes(cx);
-        let currently_selected_at_position: Vec<Entity<RgRect>> = currently_selected
-            .into_iter()
-            .filter(|node| nodes_at_position.contains(node))
-            .collect();
+    fn on_delete_selection(&mut self, _: &DeleteSelection, _window: &mut Window, cx: &mut Context<Self>) {
+        self.delete_selected_nodes(cx);
+    }
 
-        if !currently_selected_at_position.is_empty() {
-            let current_selected = &currently_selected_at_position[0];
-            let current_index = nodes_at_position.iter().position(|n| n == current_selected).unwrap();
-            let next_index = (current_index + 1) % nodes_at_position.len();
-            return Some(nodes_at_position[next_index].clone());
-        }
+    fn on_select_all(&mut self, _: &SelectAll, _window: &mut Window, cx: &mut Context<Self>) {
+        self.select_all(cx);
+    }
 
-        Some(nodes_at_position[0].clone())
+    fn on_clear_selection(&mut self, _: &ClearSelection, _window: &mut Window, cx: &mut Context<Self>) {
+        self.clear_selection(cx);
     }
 
-    fn bring_selected_nodes_to_front(&mut self, cx: &mut Context<Self>) {
-        let selected_nodes = self.get_selected_nodes(cx);
+    fn on_select_next(&mut self, _: &SelectNext, _window: &mut Window, cx: &mut Context<Self>) {
+        self.select_next_node(false, cx);
+    }
 
-        if selected_nodes.is_empty() {
-            return;
-        }
+    fn on_select_previous(&mut self, _: &SelectPrevious, _window: &mut Window, cx: &mut Context<Self>) {
+        self.select_next_node(true, cx);
+    }
 
-        let mut new_order: Vec<Entity<RgRect>> = Vec::with_capacity(self.nodes.len());
+    fn reorder_nodes(&mut self, order: &[u64], cx: &mut Context<Self>) {
+        let mut new_order = Vec::with_capacity(self.nodes.len());
+        let mut placed = HashSet::new();
 
-        for node in &self.nodes {
-            if !selected_nodes.contains(node) {
-                new_order.push(node.clone());
+        for id in order {
+            if let Some((_, node_entity)) = self.node_lookup.get(id) {
+                if placed.insert(*id) {
+                    new_order.push(node_entity.clone());
+                }
             }
         }
-
-        for node in selected_nodes {
-            new_order.push(node);
+        for node_entity in &self.nodes {
+            if !placed.contains(&node_entity.read(cx).id) {
+                new_order.push(node_entity.clone());
+            }
         }
 
         self.nodes = new_order;
+        self.rebuild_node_lookup(cx);
     }
 
-    fn show_select_handles(&mut self, cx: &mut Context<Self>) {
-        let selected_nodes = self.get_selected_nodes(cx);
-        for node_entity in &selected_nodes {
-            cx.update_entity(node_entity, |node, _| {
-                node.set_show_handles(selected_nodes.len() <= 1);
-            });
-        }
-    }
+    fn on_mouse_left_down(&mut self, event: &MouseDownEvent, window: &mut Window, cx: &mut Context<Self>) {
+        let position = event.position - self.bounds.origin;
 
-    fn clear_all_select(&mut self, cx: &mut Context<Self>) {
-        for node_entity in &self.nodes {
-            cx.update_entity(node_entity, |node, _| {
-                node.set_show_handles(true);
-            });
-        }
+        // 点击编辑框以外的地方时提交正在编辑的标签
+        self.commit_label_edit(window, cx);
+        window.focus(&self.focus_handle);
 
-        for node_entity in &self.nodes {
-            cx.update_entity(node_entity, |node, _| {
-                node.selected = false;
-            });
+        //***************缩略图：点击或拖动时平移视图*********************//
+        if self.hit_minimap(position) {
+            let transform = self.minimap_transform();
+            self.minimap_drag = Some(transform);
+            self.center_view_on_minimap(position, transform, cx);
+            return;
         }
-    }
-
-    fn toggle_node_selection(&mut self, node_entity: &Entity<RgRect>, cx: &mut Context<Self>) {
-        cx.update_entity(node_entity, |node, _| {
-            node.selected = !node.selected;
-        });
-    }
 
-    fn on_mouse_left_down(&mut self, event: &MouseDownEvent, _window: &mut Window, cx: &mut Context<Self>) {
-        let position = event.position - self.bounds.origin;
+        //***************0.按住空格拖动画布*********************//
+        if self.space_pressed {
+            self.pan_drag_start = Some((position, self.pan));
+            cx.refresh_windows();
+            return;
+        }
 
+        self.begin_transaction(cx);
         self.click_start_position = Some(position);
 
         //***************1.如果按shift*********************//
@@ -238,23 +2540,47 @@ impl RgEditor {
                 self.toggle_node_selection(&node_entity, cx);
                 self.show_select_handles(cx);
                 self.bring_selected_nodes_to_front(cx);
+            } else if let Some(index) = self.connector_at(position, cx) {
+                self.connectors[index].selected = !self.connectors[index].selected;
             }
 
             cx.refresh_windows();
             return;
         }
 
-        //***************2.如果点击手柄*********************//
+        //***************2.如果点击多选包围盒的手柄*********************//
+        if let Some(bounds) = self.selection_bounds(cx) {
+            let handle = self.detect_group_handle_at(position, bounds);
+            if handle != ResizeHandle::None {
+                let selected_nodes = self.get_selected_nodes(cx);
+                self.sync_nodes_pan_zoom(&selected_nodes, cx);
+                self.capture_drag_geometry(&selected_nodes, cx);
+                self.group_resize = Some(RgGroupResize {
+                    bounds,
+                    nodes: selected_nodes.iter()
+                        .map(|node_entity| {
+                            let geometry = cx.read_entity(node_entity, |node, _| (node.x, node.y, node.width, node.height));
+                            (node_entity.clone(), geometry)
+                        })
+                        .collect(),
+                });
+                self.drag_state = Some((selected_nodes, handle));
+                cx.refresh_windows();
+                return;
+            }
+        }
+
+        //***************3.如果点击手柄*********************//
         let mut hit_handle_node = None;
         let mut hit_handle_type = ResizeHandle::None;
 
-        for node_entity in &self.nodes {
-            let handle = cx.read_entity(node_entity, |node, _| {
+        for node_entity in self.get_nodes_at_position_with_edges(position, cx) {
+            let handle = cx.read_entity(&node_entity, |node, _| {
                 node.detect_handle_at(position)
             });
 
             if handle != ResizeHandle::None && handle != ResizeHandle::Body {
-                hit_handle_node = Some(node_entity.clone());
+                hit_handle_node = Some(node_entity);
                 hit_handle_type = handle;
                 break;
             }
@@ -264,63 +2590,182 @@ impl RgEditor {
             cx.update_entity(&node_entity, |node, _| {
                 node.start_drag(position, hit_handle_type);
             });
+            self.capture_drag_geometry(std::slice::from_ref(&node_entity), cx);
             self.drag_state = Some((vec![node_entity], hit_handle_type));
             cx.refresh_windows();
             return;
         }
 
-        //*************** 3.点击物体 *********************//
+        //*************** 4.点击物体 *********************//
         if let Some(selected_node) = self.smart_select_nodes(position, cx) {
-            let is_already_selected = cx.read_entity(&selected_node, |node, _| node.selected);
-            if !is_already_selected {
-                for node in &self.nodes {
-                    let should_select = node == &selected_node;
-                    cx.update_entity(node, |node, _| {
-                        node.selected = should_select;
-                    });
+            let (node_id, is_already_selected) = cx.read_entity(&selected_node, |node, _| (node.id, node.selected));
+            self.clear_connector_selection();
+
+            // 点击进入的组以外的节点时退出该组，双击组时进入该组
+            if self.entered_group.is_some_and(|group| !self.groups.is_descendant(RgGroupMember::Node(node_id), group)) {
+                self.entered_group = None;
+            }
+            // 双击组以外的节点时编辑标签
+            let mut entered = false;
+            let mut edit_label = false;
+            if event.click_count >= 2 {
+                if let RgGroupMember::Group(group) = self.selection_unit(node_id) {
+                    self.entered_group = Some(group);
+                    entered = true;
+                } else {
+                    edit_label = true;
                 }
             }
 
+            if !is_already_selected || entered {
+                let unit_ids = self.unit_node_ids(self.selection_unit(node_id));
+                self.set_selection(&unit_ids, cx);
+                self.key_node = None;
+            } else if self.selected_units(cx).len() > 1 {
+                self.key_node = Some(node_id);
+            }
+
             self.bring_selected_nodes_to_front(cx);
             self.show_select_handles(cx);
+            if edit_label {
+                self.begin_label_edit(node_id, window, cx);
+            }
+            cx.refresh_windows();
+            return;
+        }
+
+        //*************** 5.点击连线 *********************//
+        if let Some(index) = self.connector_at(position, cx) {
+            self.clear_all_select(cx);
+            self.connectors[index].selected = true;
             cx.refresh_windows();
             return;
         }
 
-        //*************** 4.点击空白处开始框选 *********************//
+        //*************** 6.点击空白处开始框选 *********************//
+        self.entered_group = None;
         self.is_selecting = true;
         self.selection_rect = Some((position, position));
+        self.marquee_selection.clear();
         self.clear_all_select(cx);
         cx.refresh_windows();
     }
 
     fn on_mouse_left_up(&mut self, _event: &MouseUpEvent, _window: &mut Window, cx: &mut Context<Self>) {
+        if self.minimap_drag.take().is_some() || self.pan_drag_start.take().is_some() {
+            cx.refresh_windows();
+            return;
+        }
+
         if self.is_selecting {
             self.is_selecting = false;
             self.selection_rect = None;
             self.show_select_handles(cx);
         }
 
-        if let Some((selected_nodes, _)) = &self.drag_state {
-            for node_entity in selected_nodes {
+        if let Some((selected_nodes, _)) = self.drag_state.take() {
+            for node_entity in &selected_nodes {
                 cx.update_entity(node_entity, |node, _| {
                     node.end_drag();
                 });
             }
-            self.drag_state = None;
+            self.update_spatial_index(&selected_nodes, cx);
         }
+        self.group_resize = None;
 
+        self.guides.clear();
         self.click_start_position = None;
+        self.commit_transaction(cx);
         cx.refresh_windows();
     }
 
-    fn on_mouse_right_down(&mut self, _event: &MouseDownEvent, _window: &mut Window, cx: &mut Context<Self>) {
+    fn on_mouse_right_down(&mut self, _event: &MouseDownEvent, window: &mut Window, cx: &mut Context<Self>) {
+        self.commit_label_edit(window, cx);
+        self.begin_transaction(cx);
         self.clear_all_select(cx);
+        self.commit_transaction(cx);
+        cx.refresh_windows();
+    }
+
+    fn on_mouse_middle_down(&mut self, event: &MouseDownEvent, window: &mut Window, cx: &mut Context<Self>) {
+        let position = event.position - self.bounds.origin;
+
+        window.focus(&self.focus_handle);
+        self.pan_drag_start = Some((position, self.pan));
+        cx.refresh_windows();
+    }
+
+    fn on_mouse_middle_up(&mut self, _event: &MouseUpEvent, _window: &mut Window, cx: &mut Context<Self>) {
+        self.pan_drag_start = None;
         cx.refresh_windows();
     }
 
+    fn on_scroll_wheel(&mut self, event: &ScrollWheelEvent, window: &mut Window, cx: &mut Context<Self>) {
+        let position = event.position - self.bounds.origin;
+        let delta = event.delta.pixel_delta(window.line_height());
+
+        //****************************** 1.Ctrl + 滚轮缩放 ****************************//
+        if event.modifiers.control || event.modifiers.platform {
+            let factor = (delta.y.to_f64() as f32 * WHEEL_ZOOM_SPEED).exp();
+            self.zoom_at(position, self.zoom * factor, cx);
+            return;
+        }
+
+        //****************************** 2.滚轮/触控板平移 ****************************//
+        // 普通鼠标滚轮按住 Shift 时横向平移
+        let delta = if event.modifiers.shift && !event.delta.precise() {
+            point(delta.y, delta.x)
+        } else {
+            delta
+        };
+        self.set_view(self.zoom, self.pan + delta, cx);
+    }
+
+    // 拖入 PNG、JPEG 或 SVG 文件时在鼠标位置创建图片节点，多个文件依次错开
+    fn on_drop_paths(&mut self, paths: &ExternalPaths, window: &mut Window, cx: &mut Context<Self>) {
+        let (world_x, world_y) = self.screen_to_world(window.mouse_position() - self.bounds.origin);
+        let (width, height) = IMAGE_NODE_SIZE;
+
+        let images: Vec<(RgImage, RgGeometry)> = paths.paths()
+            .iter()
+            .filter_map(RgImage::from_path)
+            .enumerate()
+            .map(|(index, image)| {
+                let offset = PASTE_OFFSET * index as f32;
+                (image, (world_x - width / 2.0 + offset, world_y - height / 2.0 + offset, width, height))
+            })
+            .collect();
+        self.add_images(images, cx);
+    }
+
+    fn on_key_down(&mut self, event: &KeyDownEvent, _window: &mut Window, cx: &mut Context<Self>) {
+        // 编辑标签时空格用于输入
+        if event.keystroke.key == "space" && !self.space_pressed && self.text_edit.is_none() {
+            self.space_pressed = true;
+            cx.refresh_windows();
+        }
+    }
+
+    fn on_key_up(&mut self, event: &KeyUpEvent, _window: &mut Window, cx: &mut Context<Self>) {
+        if event.keystroke.key == "space" {
+            self.space_pressed = false;
+            cx.refresh_windows();
+        }
+    }
+
     fn on_mouse_move(&mut self, event: &MouseMoveEvent, _window: &mut Window, cx: &mut Context<Self>) {
         let position = event.position - self.bounds.origin;
+        self.last_mouse_position = Some(position);
+
+        //****************************** 0.拖动缩略图或画布 ****************************//
+        if let Some(transform) = self.minimap_drag {
+            self.center_view_on_minimap(position, transform, cx);
+            return;
+        }
+        if let Some((start_position, start_pan)) = self.pan_drag_start {
+            self.set_view(self.zoom, start_pan + (position - start_position), cx);
+            return;
+        }
 
         //****************************** 1.框选 ****************************//
         if self.is_selecting {
@@ -329,12 +2774,23 @@ impl RgEditor {
 
                 let selection_rect = self.selection_rect.unwrap();
 
-                for node_entity in &self.nodes {
-                    let is_selected = self.is_node_in_selection_rect(node_entity, &selection_rect, cx);
-                    cx.update_entity(node_entity, |node, _| {
-                        node.selected = is_selected;
-                    });
+                // 只更新选中状态发生变化的节点，碰到组内的节点时选中整个组
+                let hits: HashSet<u64> = self.get_nodes_in_selection_rect(&selection_rect, cx)
+                    .into_iter()
+                    .flat_map(|id| self.unit_node_ids(self.selection_unit(id)))
+                    .collect();
+                for (id, selected) in self.marquee_selection.difference(&hits).map(|id| (*id, false))
+                    .chain(hits.difference(&self.marquee_selection).map(|id| (*id, true)))
+                    .collect::<Vec<_>>()
+                {
+                    if let Some(node_entity) = self.find_node(id) {
+                        cx.update_entity(&node_entity, |node, _| {
+                            node.selected = selected;
+                        });
+                    }
                 }
+                self.marquee_selection = hits;
+                self.minimap_nodes = None;
             }
             cx.refresh_windows();
             return;
@@ -346,11 +2802,13 @@ impl RgEditor {
                 let selected_nodes = self.get_selected_nodes(cx);
                 if !selected_nodes.is_empty() {
                     let nodes_to_drag = selected_nodes.clone();
+                    self.sync_nodes_pan_zoom(&nodes_to_drag, cx);
                     for node_entity in &nodes_to_drag {
                         cx.update_entity(node_entity, |node, _| {
                             node.start_drag(position, ResizeHandle::Body);
                         });
                     }
+                    self.capture_drag_geometry(&nodes_to_drag, cx);
                     self.drag_state = Some((nodes_to_drag, ResizeHandle::Body));
                 }
 
@@ -360,15 +2818,180 @@ impl RgEditor {
         }
 
         //****************************** 3.进行拖动 ****************************//
-        if let Some((selected_nodes, _handle)) = &self.drag_state {
+        if let Some((selected_nodes, handle)) = self.drag_state.clone() {
+            self.update_drag_nodes(&selected_nodes, handle, position, event.modifiers, cx);
+            let options = RgDragOptions { keep_aspect_ratio: event.modifiers.shift, ..Default::default() };
+            self.constrain_to_artboard(&selected_nodes, handle, options, cx);
+            self.invalidate_connector_routes_near(&selected_nodes, cx);
+        }
+
+        cx.refresh_windows();
+    }
+
+    fn update_drag_nodes(&mut self, selected_nodes: &[Entity<RgRect>], handle: ResizeHandle, position: Point<Pixels>, modifiers: Modifiers, cx: &mut Context<Self>) {
+        // 移动时按住 Alt 临时关闭网格吸附和智能参考线；
+        // 调整大小时 Shift 保持宽高比、Alt 以中心缩放，旋转时 Shift 按固定角度吸附
+        let resizing = handle != ResizeHandle::Body && handle != ResizeHandle::Rotate;
+        let snapping = resizing || !modifiers.alt;
+        let snap_step = (snapping && self.grid.snap_enabled).then(|| self.grid.snap_step());
+        let options = RgDragOptions {
+            snap_step,
+            snap_rotation: modifiers.shift,
+            keep_aspect_ratio: modifiers.shift,
+            from_center: modifiers.alt,
+        };
+
+        if self.group_resize.is_some() {
+            self.update_group_resize(handle, position, options, cx);
+            self.guides.clear();
+            cx.refresh_windows();
+            return;
+        }
+
+        for node_entity in selected_nodes {
+            cx.update_entity(node_entity, |node, _| {
+                node.update_drag_with_options(position, options);
+            });
+        }
+
+        // 整体移动时以第一个节点的左上角吸附，其余节点做相同偏移
+        if handle == ResizeHandle::Body && snap_step.is_some() {
+            if let Some(anchor) = selected_nodes.first() {
+                let grid = self.grid;
+                let (dx, dy) = cx.read_entity(anchor, |node, _| {
+                    (grid.snap(node.x) - node.x, grid.snap(node.y) - node.y)
+                });
+                self.offset_nodes(selected_nodes, dx, dy, cx);
+            }
+        }
+
+        //****************************** 智能参考线 ****************************//
+        self.guides.clear();
+        if !snapping || !self.smart_guides.enabled || handle == ResizeHandle::Rotate {
+            return;
+        }
+
+        // 旋转后的节点沿自身坐标轴调整大小，等比或以中心调整大小时会同时移动多条边，都不参与边缘对齐
+        if resizing
+            && (options.from_center
+                || selected_nodes.iter().any(|node_entity| {
+                    cx.read_entity(node_entity, |node, _| node.rotation != 0.0 || node.keeps_aspect_ratio(options))
+                }))
+        {
+            return;
+        }
+
+        // 只与视口内的其他节点对齐
+        let moving_ids: HashSet<u64> = selected_nodes.iter()
+            .map(|node_entity| cx.read_entity(node_entity, |node, _| node.id))
+            .collect();
+        let targets: Vec<RgGeometry> = self.spatial_index
+            .query_rect(self.visible_world_rect())
+            .into_iter()
+            .filter(|id| !moving_ids.contains(id))
+            .filter_map(|id| self.spatial_index.get(id))
+            .collect();
+        let Some(moving) = Self::union_geometry(selected_nodes, cx) else {
+            return;
+        };
+
+        let edges = RgGuideEdges::for_handle(handle);
+        let (dx, dy) = self.smart_guides.snap_offset(moving, edges, &targets, self.zoom);
+
+        if handle == ResizeHandle::Body {
+            self.offset_nodes(selected_nodes, dx, dy, cx);
+        } else {
             for node_entity in selected_nodes {
                 cx.update_entity(node_entity, |node, _| {
-                    node.update_drag(position);
+                    if let Some((x, y, width, height)) = edges.resize((node.x, node.y, node.width, node.height), (dx, dy)) {
+                        node.x = x;
+                        node.y = y;
+                        node.width = width;
+                        node.height = height;
+                    }
                 });
             }
         }
 
-        cx.refresh_windows();
+        if let Some(moving) = Self::union_geometry(selected_nodes, cx) {
+            self.guides = self.smart_guides.collect(moving, edges, &targets, self.zoom);
+        }
+    }
+
+    // 按包围盒的缩放比例同时缩放并移动每个节点，缩放比例受最小节点的最小尺寸限制。
+    // 节点沿自身坐标轴缩放，有旋转或保持宽高比的节点时按统一比例缩放，避免变形或超出包围盒
+    fn update_group_resize(&self, handle: ResizeHandle, position: Point<Pixels>, options: RgDragOptions, cx: &mut Context<Self>) {
+        let Some(group) = &self.group_resize else {
+            return;
+        };
+        let (group_x, group_y, group_width, group_height) = group.bounds;
+        if group_width <= 0.0 || group_height <= 0.0 {
+            return;
+        }
+
+        let (mut world_x, mut world_y) = self.screen_to_world(position);
+        if let Some(step) = options.snap_step.filter(|step| *step > 0.0) {
+            world_x = (world_x / step).round() * step;
+            world_y = (world_y / step).round() * step;
+        }
+
+        let min_size = group.nodes.iter().fold((MIN_NODE_SIZE, MIN_NODE_SIZE), |(min_width, min_height), (_, geometry)| {
+            (
+                min_width.max(group_width * MIN_NODE_SIZE / geometry.2.max(f32::EPSILON)),
+                min_height.max(group_height * MIN_NODE_SIZE / geometry.3.max(f32::EPSILON)),
+            )
+        });
+        let uniform = options.keep_aspect_ratio
+            || group.nodes.iter().any(|(node_entity, _)| {
+                cx.read_entity(node_entity, |node, _| node.rotation != 0.0 || node.keeps_aspect_ratio(options))
+            });
+        let options = RgDragOptions { keep_aspect_ratio: uniform, ..options };
+        let (new_x, new_y, new_width, new_height) = resize_geometry(group.bounds, handle, (world_x, world_y), options, min_size);
+        let scale_x = new_width / group_width;
+        let scale_y = new_height / group_height;
+
+        // 以节点中心计算新位置，旋转的节点保持角度不变
+        for (node_entity, (x, y, width, height)) in &group.nodes {
+            let center_x = new_x + (x + width / 2.0 - group_x) * scale_x;
+            let center_y = new_y + (y + height / 2.0 - group_y) * scale_y;
+            cx.update_entity(node_entity, |node, _| {
+                node.width = width * scale_x;
+                node.height = height * scale_y;
+                node.x = center_x - node.width / 2.0;
+                node.y = center_y - node.height / 2.0;
+            });
+        }
+    }
+
+    fn offset_nodes(&self, nodes: &[Entity<RgRect>], dx: f32, dy: f32, cx: &mut Context<Self>) {
+        if dx == 0.0 && dy == 0.0 {
+            return;
+        }
+        for node_entity in nodes {
+            cx.update_entity(node_entity, |node, _| {
+                node.x += dx;
+                node.y += dy;
+            });
+        }
+    }
+
+    fn node_geometry(node_entity: &Entity<RgRect>, cx: &mut Context<Self>) -> RgGeometry {
+        cx.read_entity(node_entity, |node, _| node.world_bounds())
+    }
+
+    // 多个节点（旋转后）的世界坐标包围盒
+    fn union_geometry(nodes: &[Entity<RgRect>], cx: &mut Context<Self>) -> Option<RgGeometry> {
+        let mut bounds: Option<(f32, f32, f32, f32)> = None;
+
+        for node_entity in nodes {
+            let (x, y, width, height) = Self::node_geometry(node_entity, cx);
+            bounds = Some(match bounds {
+                Some((min_x, min_y, max_x, max_y)) => (min_x.min(x), min_y.min(y), max_x.max(x + width), max_y.max(y + height)),
+                None => (x, y, x + width, y + height),
+            });
+        }
+
+        bounds.map(|(min_x, min_y, max_x, max_y)| (min_x, min_y, max_x - min_x, max_y - min_y))
     }
 
     fn on_bounds_changed(&mut self, new_bounds: Bounds<Pixels>, cx: &mut Context<Self>) {
@@ -383,25 +3006,192 @@ impl RgEditor {
     }
 }
 
+impl EventEmitter<RgEditorEvent> for RgEditor {}
+
 //************************************************************************** Render **********************************************************************************//
 impl Render for RgEditor {
-    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let layout_animating = self.step_layout_animation(cx);
+        let view_animating = self.step_view_animation();
+        if layout_animating || view_animating {
+            window.request_animation_frame();
+        }
+
         let view = cx.entity().clone();
         let selection_rect = self.selection_rect.clone();
         let content_bounds = self.content_bounds;
         let zoom = self.zoom;
         let pan = self.pan;
+        let grid = self.grid;
+
+        let visible_nodes = self.get_visible_nodes(cx);
+        self.sync_nodes_pan_zoom(&visible_nodes, cx);
+        let group_bounds = self.selection_bounds(cx);
+
+        // 连线跟随两端节点的当前位置，只绘制与视口相交的连线
+        self.update_connector_routes(cx);
+        let (view_x, view_y, view_width, view_height) = self.visible_world_rect();
+        let connector_routes: Vec<(RgConnector, Vec<(f32, f32)>)> = self.connectors.iter()
+            .filter_map(|connector| {
+                let points = self.connector_route(connector, cx)?;
+                let (min_x, min_y, max_x, max_y) = points.iter().fold(
+                    (f32::INFINITY, f32::INFINITY, f32::NEG_INFINITY, f32::NEG_INFINITY),
+                    |(a, b, c, d), (x, y)| (a.min(*x), b.min(*y), c.max(*x), d.max(*y)),
+                );
+                let visible = self.base_size.width <= px(0.0)
+                    || (max_x >= view_x && min_x <= view_x + view_width && max_y >= view_y && min_y <= view_y + view_height);
+                visible.then(|| (connector.clone(), points))
+            })
+            .collect();
+        let entered_group_bounds = self.entered_group.and_then(|group| {
+            let nodes: Vec<Entity<RgRect>> = self.unit_node_ids(RgGroupMember::Group(group))
+                .into_iter()
+                .filter_map(|id| self.find_node(id))
+                .collect();
+            Self::union_geometry(&nodes, cx)
+        });
+        // 标签编辑器覆盖在节点上，节点被删除时结束编辑
+        let label_editor = match self.text_edit.as_ref() {
+            Some((text_editor, _)) => {
+                let node_id = text_editor.read(cx).node_id;
+                self.find_node(node_id).map(|node_entity| {
+                    let (x, y, width, height, style) = cx.read_entity(&node_entity, |node, _| {
+                        (node.x, node.y, node.width, node.height, node.style)
+                    });
+                    cx.update_entity(text_editor, |text_editor, _| {
+                        text_editor.style = style;
+                        text_editor.zoom = zoom;
+                    });
+                    div()
+                        .absolute()
+                        .left(pan.x + px(x * zoom))
+                        .top(pan.y + px(y * zoom))
+                        .w(px(width * zoom))
+                        .h(px(height * zoom))
+                        .px(px(LABEL_PADDING * zoom))
+                        .child(text_editor.clone())
+                })
+            }
+            None => None,
+        };
+        if label_editor.is_none() {
+            self.text_edit = None;
+        }
+        let key_unit_bounds = self.key_unit(cx).and_then(|unit| {
+            let nodes: Vec<Entity<RgRect>> = self.unit_node_ids(unit)
+                .into_iter()
+                .filter_map(|id| self.find_node(id))
+                .collect();
+            Self::union_geometry(&nodes, cx)
+        });
+
+        let cursor = if self.pan_drag_start.is_some() {
+            CursorStyle::ClosedHand
+        } else if self.space_pressed {
+            CursorStyle::OpenHand
+        } else {
+            CursorStyle::Arrow
+        };
 
         let mut element =
             div()
             .size_full()
             .relative()
             .bg(rgb(0xffffff))
+            .track_focus(&self.focus_handle)
+            .key_context("RgEditor")
+            .cursor(cursor)
+            .on_action(cx.listener(Self::on_undo))
+            .on_action(cx.listener(Self::on_redo))
+            .on_action(cx.listener(Self::on_nudge_left))
+            .on_action(cx.listener(Self::on_nudge_right))
+            .on_action(cx.listener(Self::on_nudge_up))
+            .on_action(cx.listener(Self::on_nudge_down))
+            .on_action(cx.listener(Self::on_nudge_left_large))
+            .on_action(cx.listener(Self::on_nudge_right_large))
+            .on_action(cx.listener(Self::on_nudge_up_large))
+            .on_action(cx.listener(Self::on_nudge_down_large))
+            .on_action(cx.listener(Self::on_delete_selection))
+            .on_action(cx.listener(Self::on_select_all))
+            .on_action(cx.listener(Self::on_clear_selection))
+            .on_action(cx.listener(Self::on_select_next))
+            .on_action(cx.listener(Self::on_select_previous))
+            .on_action(cx.listener(Self::on_copy))
+            .on_action(cx.listener(Self::on_cut))
+            .on_action(cx.listener(Self::on_paste))
+            .on_action(cx.listener(Self::on_paste_at_cursor))
+            .on_action(cx.listener(Self::on_duplicate))
+            .on_action(cx.listener(Self::on_group_selection))
+            .on_action(cx.listener(Self::on_ungroup_selection))
+            .on_action(cx.listener(Self::on_align_left))
+            .on_action(cx.listener(Self::on_align_horizontal_center))
+            .on_action(cx.listener(Self::on_align_right))
+            .on_action(cx.listener(Self::on_align_top))
+            .on_action(cx.listener(Self::on_align_vertical_center))
+            .on_action(cx.listener(Self::on_align_bottom))
+            .on_action(cx.listener(Self::on_distribute_horizontal_spacing))
+            .on_action(cx.listener(Self::on_distribute_vertical_spacing))
+            .on_action(cx.listener(Self::on_distribute_horizontal_centers))
+            .on_action(cx.listener(Self::on_distribute_vertical_centers))
+            .on_action(cx.listener(Self::on_zoom_in))
+            .on_action(cx.listener(Self::on_zoom_out))
+            .on_action(cx.listener(Self::on_zoom_to_actual_size))
+            .on_action(cx.listener(Self::on_zoom_to_fit))
+            .on_action(cx.listener(Self::on_zoom_to_selection))
             .on_mouse_down(MouseButton::Left, cx.listener(Self::on_mouse_left_down))
             .on_mouse_up(MouseButton::Left, cx.listener(Self::on_mouse_left_up))
             .on_mouse_down(MouseButton::Right, cx.listener(Self::on_mouse_right_down))
+            .on_mouse_down(MouseButton::Middle, cx.listener(Self::on_mouse_middle_down))
+            .on_mouse_up(MouseButton::Middle, cx.listener(Self::on_mouse_middle_up))
+            .on_scroll_wheel(cx.listener(Self::on_scroll_wheel))
+            .on_key_down(cx.listener(Self::on_key_down))
+            .on_key_up(cx.listener(Self::on_key_up))
             .on_mouse_move(cx.listener(Self::on_mouse_move))
-            .children(self.nodes.iter().cloned())
+            .on_drop(cx.listener(Self::on_drop_paths))
+            .child(
+                canvas(
+                    |_, _, _| {},
+                    move |bounds, _, window, _| {
+                        grid.paint(bounds, zoom, pan, window);
+                    },
+                )
+                .absolute()
+                .size_full()
+            )
+            .child({
+                let connector_routes = connector_routes.clone();
+                canvas(
+                    |_, _, _| {},
+                    move |bounds, _, window, _| {
+                        for (connector, points) in &connector_routes {
+                            let points: Vec<Point<Pixels>> = points.iter()
+                                .map(|(x, y)| pan + point(px(x * zoom), px(y * zoom)))
+                                .collect();
+                            connector.paint(&points, bounds, window);
+                        }
+                    },
+                )
+                .absolute()
+                .size_full()
+            })
+            .children(connector_routes.iter().filter_map(|(connector, points)| {
+                let label = connector.data.label.clone().filter(|label| !label.is_empty())?;
+                let (x, y) = RgConnector::midpoint(points)?;
+                Some(
+                    div()
+                        .absolute()
+                        .left(pan.x + px(x * zoom))
+                        .top(pan.y + px(y * zoom) - px(8.0))
+                        .px(px(3.0))
+                        .bg(white())
+                        .text_color(black())
+                        .text_size(px(12.0))
+                        .line_height(px(16.0))
+                        .child(label)
+                )
+            }))
+            .children(visible_nodes)
+            .children(label_editor)
             .child({
                 canvas(
                     move |bounds, _, cx| {
@@ -440,6 +3230,108 @@ impl Render for RgEditor {
                 .bg(rgba(0xff000010))
         );
 
+        for guide in &self.guides {
+            let guide_color = rgb(0xff3377);
+            let position = match guide.axis {
+                RgGuideAxis::Vertical => pan.x + px(guide.position * zoom),
+                RgGuideAxis::Horizontal => pan.y + px(guide.position * zoom),
+            };
+            let (start, end) = match guide.axis {
+                RgGuideAxis::Vertical => (pan.y + px(guide.start * zoom), pan.y + px(guide.end * zoom)),
+                RgGuideAxis::Horizontal => (pan.x + px(guide.start * zoom), pan.x + px(guide.end * zoom)),
+            };
+
+            let line = match guide.axis {
+                RgGuideAxis::Vertical => div().absolute().left(position).top(start).w(px(1.0)).h(end - start),
+                RgGuideAxis::Horizontal => div().absolute().left(start).top(position).w(end - start).h(px(1.0)),
+            };
+            element = element.child(line.bg(guide_color));
+
+            if let Some((label_position, distance)) = guide.distance {
+                let label_position = match guide.axis {
+                    RgGuideAxis::Vertical => pan.y + px(label_position * zoom),
+                    RgGuideAxis::Horizontal => pan.x + px(label_position * zoom),
+                };
+                let (left, top) = match guide.axis {
+                    RgGuideAxis::Vertical => (position + px(4.0), label_position - px(8.0)),
+                    RgGuideAxis::Horizontal => (label_position - px(12.0), position + px(4.0)),
+                };
+
+                element = element.child(
+                    div()
+                        .absolute()
+                        .left(left)
+                        .top(top)
+                        .px(px(3.0))
+                        .bg(guide_color)
+                        .rounded(px(2.0))
+                        .text_color(white())
+                        .text_size(px(11.0))
+                        .line_height(px(16.0))
+                        .child(format!("{:.0}", distance))
+                );
+            }
+        }
+
+        if let Some((x, y, width, height)) = entered_group_bounds {
+            element = element.child(
+                div()
+                    .absolute()
+                    .left(pan.x + px(x * zoom - 4.0))
+                    .top(pan.y + px(y * zoom - 4.0))
+                    .w(px(width * zoom + 8.0))
+                    .h(px(height * zoom + 8.0))
+                    .border_1()
+                    .border_color(rgba(0x50505088))
+                    .border_dashed()
+            );
+        }
+
+        // 关键对象用加粗的外框标出
+        if let Some((x, y, width, height)) = key_unit_bounds {
+            element = element.child(
+                div()
+                    .absolute()
+                    .left(pan.x + px(x * zoom - 2.0))
+                    .top(pan.y + px(y * zoom - 2.0))
+                    .w(px(width * zoom + 4.0))
+                    .h(px(height * zoom + 4.0))
+                    .border_2()
+                    .border_color(rgb(0x008aff))
+            );
+        }
+
+        if let Some(bounds) = group_bounds {
+            let (x, y, width, height) = bounds;
+            let group_color = rgb(0x008aff);
+
+            element = element.child(
+                div()
+                    .absolute()
+                    .left(pan.x + px(x * zoom))
+                    .top(pan.y + px(y * zoom))
+                    .w(px(width * zoom))
+                    .h(px(height * zoom))
+                    .border_1()
+                    .border_color(group_color)
+                    .border_dashed()
+            );
+
+            for (_, handle_position) in self.group_handle_positions(bounds) {
+                element = element.child(
+                    div()
+                        .absolute()
+                        .left(handle_position.x - px(GROUP_HANDLE_SIZE / 2.0))
+                        .top(handle_position.y - px(GROUP_HANDLE_SIZE / 2.0))
+                        .w(px(GROUP_HANDLE_SIZE))
+                        .h(px(GROUP_HANDLE_SIZE))
+                        .bg(white())
+                        .border_1()
+                        .border_color(group_color)
+                );
+            }
+        }
+
         if let Some((start, end)) = selection_rect {
             let rect_left = start.x.min(end.x);
             let rect_right = start.x.max(end.x);
@@ -461,6 +3353,30 @@ impl Render for RgEditor {
             );
         }
 
+        // 缩略图在最上层
+        if self.minimap.visible {
+            let minimap = self.minimap;
+            let panel = minimap.panel_bounds(self.bounds.size);
+            let nodes = self.minimap_nodes(cx);
+            let transform = self.minimap_drag.unwrap_or_else(|| self.minimap_transform());
+            let content_bounds = (min_x, min_y, max_x - min_x, max_y - min_y);
+            let viewport = self.visible_world_rect();
+
+            element = element.child(
+                canvas(
+                    |_, _, _| {},
+                    move |bounds, _, window, _| {
+                        minimap.paint(bounds, transform, &nodes, content_bounds, viewport, window);
+                    },
+                )
+                .absolute()
+                .left(panel.origin.x)
+                .top(panel.origin.y)
+                .w(panel.size.width)
+                .h(panel.size.height)
+            );
+        }
+
         element
     }
 }
@@ -0,0 +1,87 @@
+use gpui::*;
+
+//************************************************************************** RgGrid **********************************************************************************//
+const MIN_GRID_SCREEN_SPACING: f32 = 6.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RgGrid {
+    pub visible: bool,
+    pub snap_enabled: bool,
+    /// 主网格间距（世界坐标）
+    pub spacing: f32,
+    /// 每个主网格的细分数，吸附步长为 spacing / subdivisions
+    pub subdivisions: u32,
+}
+
+//************************************************************************** Trait **********************************************************************************//
+impl RgGrid {
+    pub fn snap_step(&self) -> f32 {
+        self.spacing / self.subdivisions.max(1) as f32
+    }
+
+    pub fn snap(&self, value: f32) -> f32 {
+        let step = self.snap_step();
+        if step <= 0.0 {
+            return value;
+        }
+        (value / step).round() * step
+    }
+
+    // 绘制网格，缩小到线条过密时逐级合并主网格，并隐藏细分线
+    pub fn paint(&self, bounds: Bounds<Pixels>, zoom: f32, pan: Point<Pixels>, window: &mut Window) {
+        if !self.visible || self.spacing <= 0.0 || zoom <= 0.0 {
+            return;
+        }
+
+        let minor_step = self.snap_step() * zoom;
+        let mut major_step = self.spacing * zoom;
+        while major_step < MIN_GRID_SCREEN_SPACING {
+            major_step *= 2.0;
+        }
+
+        if self.subdivisions > 1 && minor_step >= MIN_GRID_SCREEN_SPACING {
+            Self::paint_lines(bounds, pan, minor_step, rgba(0x00000010), window);
+        }
+        Self::paint_lines(bounds, pan, major_step, rgba(0x00000024), window);
+    }
+
+    fn paint_lines(bounds: Bounds<Pixels>, pan: Point<Pixels>, step: f32, color: Rgba, window: &mut Window) {
+        let left = bounds.origin.x.to_f64() as f32;
+        let top = bounds.origin.y.to_f64() as f32;
+        let right = left + bounds.size.width.to_f64() as f32;
+        let bottom = top + bounds.size.height.to_f64() as f32;
+
+        // 世界坐标原点在屏幕上的位置
+        let origin_x = left + pan.x.to_f64() as f32;
+        let origin_y = top + pan.y.to_f64() as f32;
+
+        let mut x = origin_x + ((left - origin_x) / step).ceil() * step;
+        while x <= right {
+            window.paint_quad(fill(
+                Bounds::new(point(px(x), bounds.origin.y), size(px(1.0), bounds.size.height)),
+                color,
+            ));
+            x += step;
+        }
+
+        let mut y = origin_y + ((top - origin_y) / step).ceil() * step;
+        while y <= bottom {
+            window.paint_quad(fill(
+                Bounds::new(point(bounds.origin.x, px(y)), size(bounds.size.width, px(1.0))),
+                color,
+            ));
+            y += step;
+        }
+    }
+}
+
+impl Default for RgGrid {
+    fn default() -> Self {
+        Self {
+            visible: true,
+            snap_enabled: false,
+            spacing: 50.0,
+            subdivisions: 5,
+        }
+    }
+}
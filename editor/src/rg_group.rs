@@ -0,0 +1,346 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::*;
+
+//************************************************************************** RgGroups **********************************************************************************//
+/// 组的成员：节点或子组
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RgGroupMember {
+    Node(u64),
+    Group(u64),
+}
+
+/// 节点的分组层级。节点本身仍按 z 序平铺保存在 RgEditor 中，这里只记录组与成员的从属关系
+#[derive(Debug, Default)]
+pub struct RgGroups {
+    groups: HashMap<u64, RgGroupData>,
+    parents: HashMap<RgGroupMember, u64>,
+    next_id: u64,
+}
+
+//************************************************************************** Trait **********************************************************************************//
+impl RgGroups {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.groups.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.groups.clear();
+        self.parents.clear();
+        self.next_id = 0;
+    }
+
+    pub fn get(&self, id: u64) -> Option<&RgGroupData> {
+        self.groups.get(&id)
+    }
+
+    pub fn parent(&self, member: RgGroupMember) -> Option<u64> {
+        self.parents.get(&member).copied()
+    }
+
+    /// 分配一个未使用的组 id
+    pub fn allocate_id(&mut self) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        id
+    }
+
+    /// 替换全部分组数据，例如加载文档时
+    pub fn load(&mut self, groups: &[RgGroupData]) {
+        self.clear();
+        self.extend(groups);
+    }
+
+    /// 添加一组已经相互关联好的分组数据，例如粘贴时
+    pub fn extend(&mut self, groups: &[RgGroupData]) {
+        for group in groups {
+            for member in members(group) {
+                self.parents.insert(member, group.id);
+            }
+            self.next_id = self.next_id.max(group.id + 1);
+            self.groups.insert(group.id, group.clone());
+        }
+    }
+
+    /// 创建组：成员从原来的父组中移出，新组放入 parent 中。返回成员原来的父组，用于撤销
+    pub fn insert(&mut self, group: RgGroupData, parent: Option<u64>) -> Vec<(RgGroupMember, Option<u64>)> {
+        let mut member_parents = Vec::new();
+        for member in members(&group) {
+            let old_parent = self.parents.insert(member, group.id);
+            if let Some(old_group) = old_parent.and_then(|old_parent| self.groups.get_mut(&old_parent)) {
+                remove_member(old_group, member);
+            }
+            member_parents.push((member, old_parent));
+        }
+
+        if let Some(parent) = parent.filter(|parent| self.groups.contains_key(parent)) {
+            self.groups.entry(parent).and_modify(|parent_group| parent_group.groups.push(group.id));
+            self.parents.insert(RgGroupMember::Group(group.id), parent);
+        }
+
+        self.next_id = self.next_id.max(group.id + 1);
+        self.groups.insert(group.id, group);
+        member_parents
+    }
+
+    /// 把成员移到 parent 组中（None 为顶层），例如撤销创建组时恢复成员原来的父组
+    pub fn move_member(&mut self, member: RgGroupMember, parent: Option<u64>) {
+        let parent = parent.filter(|parent| self.groups.contains_key(parent));
+        let old_parent = match parent {
+            Some(parent) => self.parents.insert(member, parent),
+            None => self.parents.remove(&member),
+        };
+        if old_parent == parent {
+            return;
+        }
+
+        if let Some(old_group) = old_parent.and_then(|old_parent| self.groups.get_mut(&old_parent)) {
+            remove_member(old_group, member);
+        }
+        if let Some(parent_group) = parent.and_then(|parent| self.groups.get_mut(&parent)) {
+            match member {
+                RgGroupMember::Node(id) => parent_group.nodes.push(id),
+                RgGroupMember::Group(id) => parent_group.groups.push(id),
+            }
+        }
+    }
+
+    /// 解散组，成员移到组原来的父组中。返回组数据和父组 id，用于撤销
+    pub fn remove(&mut self, id: u64) -> Option<(RgGroupData, Option<u64>)> {
+        let group = self.groups.remove(&id)?;
+        let parent = self.parents.remove(&RgGroupMember::Group(id));
+
+        if let Some(parent_group) = parent.and_then(|parent| self.groups.get_mut(&parent)) {
+            remove_member(parent_group, RgGroupMember::Group(id));
+            parent_group.nodes.extend(group.nodes.iter().copied());
+            parent_group.groups.extend(group.groups.iter().copied());
+        }
+
+        for member in members(&group) {
+            match parent {
+                Some(parent) => self.parents.insert(member, parent),
+                None => self.parents.remove(&member),
+            };
+        }
+
+        Some((group, parent))
+    }
+
+    /// member 是否在 ancestor 组内（任意层级）
+    pub fn is_descendant(&self, member: RgGroupMember, ancestor: u64) -> bool {
+        let mut current = self.parent(member);
+        while let Some(parent) = current {
+            if parent == ancestor {
+                return true;
+            }
+            current = self.parent(RgGroupMember::Group(parent));
+        }
+        false
+    }
+
+    /// 在 scope 组内（None 为顶层）包含该节点的选择单元，即 scope 的直接成员。节点不在 scope 内时返回 None
+    pub fn unit_for_node(&self, node: u64, scope: Option<u64>) -> Option<RgGroupMember> {
+        let mut member = RgGroupMember::Node(node);
+        loop {
+            match self.parent(member) {
+                parent if parent == scope => return Some(member),
+                Some(parent) => member = RgGroupMember::Group(parent),
+                None => return None,
+            }
+        }
+    }
+
+    /// 成员包含的全部节点 id（任意层级）
+    pub fn leaf_nodes(&self, member: RgGroupMember) -> Vec<u64> {
+        let mut result = Vec::new();
+        let mut stack = vec![member];
+
+        while let Some(member) = stack.pop() {
+            match member {
+                RgGroupMember::Node(id) => result.push(id),
+                RgGroupMember::Group(id) => {
+                    if let Some(group) = self.groups.get(&id) {
+                        stack.extend(members(group));
+                    }
+                }
+            }
+        }
+
+        result
+    }
+
+    /// 导出分组数据，只保留 keep 中的节点，去掉因此变空的组。子组排在父组之前
+    pub fn to_data(&self, keep: impl Fn(u64) -> bool) -> Vec<RgGroupData> {
+        let mut result = Vec::new();
+        let mut exported = HashSet::new();
+
+        let mut roots: Vec<u64> = self.groups.keys()
+            .copied()
+            .filter(|id| self.parent(RgGroupMember::Group(*id)).is_none_or(|parent| !self.groups.contains_key(&parent)))
+            .collect();
+        roots.sort_unstable();
+
+        for root in roots {
+            self.export_group(root, &keep, &mut exported, &mut result);
+        }
+
+        result
+    }
+
+    // 后序遍历导出组，返回该组是否被保留
+    fn export_group(&self, id: u64, keep: &impl Fn(u64) -> bool, exported: &mut HashSet<u64>, result: &mut Vec<RgGroupData>) -> bool {
+        let Some(group) = self.groups.get(&id) else {
+            return false;
+        };
+        if !exported.insert(id) {
+            return false;
+        }
+
+        let groups: Vec<u64> = group.groups.iter()
+            .copied()
+            .filter(|child| self.export_group(*child, keep, exported, result))
+            .collect();
+        let nodes: Vec<u64> = group.nodes.iter().copied().filter(|node| keep(*node)).collect();
+
+        if nodes.is_empty() && groups.is_empty() {
+            return false;
+        }
+
+        result.push(RgGroupData { id, nodes, groups });
+        true
+    }
+}
+
+fn members(group: &RgGroupData) -> impl Iterator<Item = RgGroupMember> + '_ {
+    group.nodes.iter()
+        .map(|id| RgGroupMember::Node(*id))
+        .chain(group.groups.iter().map(|id| RgGroupMember::Group(*id)))
+}
+
+fn remove_member(group: &mut RgGroupData, member: RgGroupMember) {
+    match member {
+        RgGroupMember::Node(id) => group.nodes.retain(|node| *node != id),
+        RgGroupMember::Group(id) => group.groups.retain(|child| *child != id),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn group(id: u64, nodes: &[u64], groups: &[u64]) -> RgGroupData {
+        RgGroupData { id, nodes: nodes.to_vec(), groups: groups.to_vec() }
+    }
+
+    fn sorted(mut ids: Vec<u64>) -> Vec<u64> {
+        ids.sort_unstable();
+        ids
+    }
+
+    // 每个组的成员都指向该组，且每条父子关系都出现在父组的成员中
+    fn assert_consistent(groups: &RgGroups) {
+        for (id, data) in &groups.groups {
+            for member in members(data) {
+                assert_eq!(groups.parent(member), Some(*id), "{member:?} in {id}");
+            }
+        }
+        for (member, parent) in &groups.parents {
+            let data = groups.get(*parent).unwrap_or_else(|| panic!("{member:?} has dangling parent {parent}"));
+            assert!(members(data).any(|other| other == *member), "{member:?} missing from {parent}");
+        }
+    }
+
+    #[test]
+    fn group_and_ungroup_round_trip() {
+        let mut groups = RgGroups::new();
+        groups.insert(group(0, &[1, 2], &[]), None);
+        groups.insert(group(1, &[3, 4], &[]), None);
+
+        // 跨组合并，撤销时成员回到原来的父组
+        let member_parents = groups.insert(group(2, &[2, 3, 5], &[]), None);
+        assert_eq!(groups.get(0).unwrap().nodes, vec![1]);
+        assert_eq!(groups.get(1).unwrap().nodes, vec![4]);
+        assert_consistent(&groups);
+
+        assert_eq!(groups.remove(2), Some((group(2, &[2, 3, 5], &[]), None)));
+        for (member, parent) in member_parents {
+            groups.move_member(member, parent);
+        }
+        assert_consistent(&groups);
+        assert_eq!(sorted(groups.get(0).unwrap().nodes.clone()), vec![1, 2]);
+        assert_eq!(sorted(groups.get(1).unwrap().nodes.clone()), vec![3, 4]);
+        assert_eq!(groups.parent(RgGroupMember::Node(5)), None);
+    }
+
+    #[test]
+    fn nested_group_round_trip() {
+        let mut groups = RgGroups::new();
+        groups.insert(group(0, &[1, 2, 3], &[]), None);
+        let member_parents = groups.insert(group(1, &[1, 2], &[]), Some(0));
+        assert_eq!(groups.parent(RgGroupMember::Group(1)), Some(0));
+        assert_eq!(groups.unit_for_node(1, Some(0)), Some(RgGroupMember::Group(1)));
+        assert_eq!(groups.unit_for_node(1, None), Some(RgGroupMember::Group(0)));
+        assert_consistent(&groups);
+
+        // 解散后再按撤销记录恢复
+        let (data, parent) = groups.remove(1).unwrap();
+        assert_eq!(parent, Some(0));
+        assert_eq!(sorted(groups.get(0).unwrap().nodes.clone()), vec![1, 2, 3]);
+        assert!(groups.get(0).unwrap().groups.is_empty());
+        assert_consistent(&groups);
+
+        assert_eq!(groups.insert(data, parent), member_parents);
+        assert_eq!(groups.get(0).unwrap().groups, vec![1]);
+        assert_consistent(&groups);
+    }
+
+    #[test]
+    fn remove_leaves_no_dangling_ids() {
+        let mut groups = RgGroups::new();
+        groups.insert(group(0, &[1, 2], &[]), None);
+        groups.insert(group(1, &[3], &[0]), None);
+
+        groups.remove(1);
+        assert_consistent(&groups);
+        assert_eq!(groups.parent(RgGroupMember::Group(0)), None);
+        assert_eq!(groups.parent(RgGroupMember::Node(3)), None);
+
+        groups.remove(0);
+        assert!(groups.is_empty());
+        assert!(groups.parents.is_empty());
+        assert_eq!(groups.remove(0), None);
+    }
+
+    #[test]
+    fn move_member_between_groups() {
+        let mut groups = RgGroups::new();
+        groups.insert(group(0, &[1], &[]), None);
+        groups.insert(group(1, &[2], &[]), None);
+
+        groups.move_member(RgGroupMember::Node(1), Some(1));
+        assert_consistent(&groups);
+        assert!(groups.get(0).unwrap().nodes.is_empty());
+        assert_eq!(groups.get(1).unwrap().nodes, vec![2, 1]);
+
+        // 目标组不存在时移到顶层
+        groups.move_member(RgGroupMember::Node(2), Some(9));
+        assert_consistent(&groups);
+        assert_eq!(groups.parent(RgGroupMember::Node(2)), None);
+        assert_eq!(groups.get(1).unwrap().nodes, vec![1]);
+    }
+
+    #[test]
+    fn to_data_drops_empty_groups() {
+        let mut groups = RgGroups::new();
+        groups.insert(group(0, &[1, 2], &[]), None);
+        groups.insert(group(1, &[3], &[0]), None);
+
+        assert_eq!(groups.to_data(|id| id == 3), vec![group(1, &[3], &[])]);
+        assert_eq!(groups.to_data(|id| id != 3), vec![group(0, &[1, 2], &[]), group(1, &[], &[0])]);
+        assert_eq!(sorted(groups.leaf_nodes(RgGroupMember::Group(1))), vec![1, 2, 3]);
+    }
+}
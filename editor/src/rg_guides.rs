@@ -0,0 +1,277 @@
+use crate::*;
+
+//************************************************************************** RgSmartGuides **********************************************************************************//
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RgGuideAxis {
+    /// 竖直参考线，position 为世界坐标 x
+    Vertical,
+    /// 水平参考线，position 为世界坐标 y
+    Horizontal,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RgGuide {
+    pub axis: RgGuideAxis,
+    pub position: f32,
+    pub start: f32,
+    pub end: f32,
+    /// 与最近对齐节点之间的间距 (标注位置, 间距)，标注位置为参考线方向上的世界坐标
+    pub distance: Option<(f32, f32)>,
+}
+
+/// 参与对齐的边：[起始边, 中心, 结束边]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RgGuideEdges {
+    pub x: [bool; 3],
+    pub y: [bool; 3],
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RgSmartGuides {
+    pub enabled: bool,
+    /// 吸附距离（屏幕像素）
+    pub tolerance: f32,
+}
+
+//************************************************************************** Trait **********************************************************************************//
+impl RgGuideEdges {
+    // 整体移动时所有边都参与对齐，调整大小时只有正在移动的边参与
+    pub fn for_handle(handle: ResizeHandle) -> Self {
+        let (x, y) = match handle {
+            ResizeHandle::Body => ([true, true, true], [true, true, true]),
+            ResizeHandle::TopLeft => ([true, false, false], [true, false, false]),
+            ResizeHandle::Top => ([false, false, false], [true, false, false]),
+            ResizeHandle::TopRight => ([false, false, true], [true, false, false]),
+            ResizeHandle::Right => ([false, false, true], [false, false, false]),
+            ResizeHandle::BottomRight => ([false, false, true], [false, false, true]),
+            ResizeHandle::Bottom => ([false, false, false], [false, false, true]),
+            ResizeHandle::BottomLeft => ([true, false, false], [false, false, true]),
+            ResizeHandle::Left => ([true, false, false], [false, false, false]),
+            _ => ([false, false, false], [false, false, false]),
+        };
+        Self { x, y }
+    }
+
+    /// 按吸附偏移移动参与对齐的边，结果小于 MIN_NODE_SIZE 时返回 None
+    pub fn resize(&self, geometry: RgGeometry, (dx, dy): (f32, f32)) -> Option<RgGeometry> {
+        let (mut x, mut y, mut width, mut height) = geometry;
+        if self.x[0] {
+            x += dx;
+            width -= dx;
+        } else if self.x[2] {
+            width += dx;
+        }
+        if self.y[0] {
+            y += dy;
+            height -= dy;
+        } else if self.y[2] {
+            height += dy;
+        }
+        (width >= MIN_NODE_SIZE && height >= MIN_NODE_SIZE).then_some((x, y, width, height))
+    }
+}
+
+impl RgSmartGuides {
+    /// 计算让 moving 与 targets 对齐所需的偏移 (dx, dy)，超出吸附距离的方向返回 0
+    pub fn snap_offset(&self, moving: RgGeometry, edges: RgGuideEdges, targets: &[RgGeometry], zoom: f32) -> (f32, f32) {
+        if !self.enabled || zoom <= 0.0 {
+            return (0.0, 0.0);
+        }
+
+        let tolerance = self.tolerance / zoom;
+        let dx = Self::best_offset(x_lines(moving), edges.x, targets.iter().map(|t| x_lines(*t)), tolerance);
+        let dy = Self::best_offset(y_lines(moving), edges.y, targets.iter().map(|t| y_lines(*t)), tolerance);
+
+        (dx.unwrap_or(0.0), dy.unwrap_or(0.0))
+    }
+
+    /// 收集 moving 当前已经对齐的参考线
+    pub fn collect(&self, moving: RgGeometry, edges: RgGuideEdges, targets: &[RgGeometry], zoom: f32) -> Vec<RgGuide> {
+        if !self.enabled || zoom <= 0.0 {
+            return Vec::new();
+        }
+
+        let epsilon = 0.5 / zoom;
+        let mut guides = Vec::new();
+
+        for (axis, lines, active) in [
+            (RgGuideAxis::Vertical, x_lines(moving), edges.x),
+            (RgGuideAxis::Horizontal, y_lines(moving), edges.y),
+        ] {
+            for (index, line) in lines.iter().enumerate() {
+                if !active[index] {
+                    continue;
+                }
+                if let Some(guide) = Self::guide_at(axis, *line, moving, targets, epsilon) {
+                    if !guides.iter().any(|g: &RgGuide| g.axis == axis && (g.position - guide.position).abs() < epsilon) {
+                        guides.push(guide);
+                    }
+                }
+            }
+        }
+
+        guides
+    }
+
+    fn best_offset(moving: [f32; 3], active: [bool; 3], targets: impl Iterator<Item = [f32; 3]>, tolerance: f32) -> Option<f32> {
+        let mut best: Option<f32> = None;
+
+        for target in targets {
+            for target_line in target {
+                for (index, moving_line) in moving.iter().enumerate() {
+                    if !active[index] {
+                        continue;
+                    }
+                    let offset = target_line - moving_line;
+                    if offset.abs() <= tolerance && best.is_none_or(|b| offset.abs() < b.abs()) {
+                        best = Some(offset);
+                    }
+                }
+            }
+        }
+
+        best
+    }
+
+    fn guide_at(axis: RgGuideAxis, line: f32, moving: RgGeometry, targets: &[RgGeometry], epsilon: f32) -> Option<RgGuide> {
+        // 沿参考线方向的区间 (起点, 终点)
+        let span = |rect: RgGeometry| match axis {
+            RgGuideAxis::Vertical => (rect.1, rect.1 + rect.3),
+            RgGuideAxis::Horizontal => (rect.0, rect.0 + rect.2),
+        };
+        let lines = |rect: RgGeometry| match axis {
+            RgGuideAxis::Vertical => x_lines(rect),
+            RgGuideAxis::Horizontal => y_lines(rect),
+        };
+
+        let (moving_start, moving_end) = span(moving);
+        let mut start = moving_start;
+        let mut end = moving_end;
+        let mut distance: Option<(f32, f32)> = None;
+        let mut matched = false;
+
+        for target in targets {
+            if !lines(*target).iter().any(|t| (t - line).abs() < epsilon) {
+                continue;
+            }
+            matched = true;
+
+            let (target_start, target_end) = span(*target);
+            start = start.min(target_start);
+            end = end.max(target_end);
+
+            let gap = if target_start >= moving_end {
+                Some(((moving_end + target_start) / 2.0, target_start - moving_end))
+            } else if target_end <= moving_start {
+                Some(((target_end + moving_start) / 2.0, moving_start - target_end))
+            } else {
+                None
+            };
+            if let Some(gap) = gap {
+                if distance.is_none_or(|d| gap.1 < d.1) {
+                    distance = Some(gap);
+                }
+            }
+        }
+
+        matched.then_some(RgGuide {
+            axis,
+            position: line,
+            start,
+            end,
+            distance,
+        })
+    }
+}
+
+impl Default for RgSmartGuides {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            tolerance: 6.0,
+        }
+    }
+}
+
+fn x_lines(rect: RgGeometry) -> [f32; 3] {
+    [rect.0, rect.0 + rect.2 / 2.0, rect.0 + rect.2]
+}
+
+fn y_lines(rect: RgGeometry) -> [f32; 3] {
+    [rect.1, rect.1 + rect.3 / 2.0, rect.1 + rect.3]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TARGET: RgGeometry = (100.0, 100.0, 100.0, 50.0);
+
+    fn guides() -> RgSmartGuides {
+        RgSmartGuides { enabled: true, tolerance: 6.0 }
+    }
+
+    #[test]
+    fn snaps_edges_and_centres_within_tolerance() {
+        let body = RgGuideEdges::for_handle(ResizeHandle::Body);
+
+        // 左边对齐左边，上边对齐下边
+        let moving = (104.0, 153.0, 40.0, 40.0);
+        assert_eq!(guides().snap_offset(moving, body, &[TARGET], 1.0), (-4.0, -3.0));
+
+        // 中心对齐中心，取最近的一条线
+        let moving = (128.0, 300.0, 40.0, 40.0);
+        assert_eq!(guides().snap_offset(moving, body, &[TARGET], 1.0), (2.0, 0.0));
+
+        // 吸附后生成参考线，并标注两个节点之间的间距
+        let snapped = (130.0, 300.0, 40.0, 40.0);
+        let collected = guides().collect(snapped, body, &[TARGET], 1.0);
+        assert_eq!(collected, vec![RgGuide {
+            axis: RgGuideAxis::Vertical,
+            position: 150.0,
+            start: 100.0,
+            end: 340.0,
+            distance: Some((225.0, 150.0)),
+        }]);
+    }
+
+    #[test]
+    fn no_snap_outside_tolerance() {
+        let body = RgGuideEdges::for_handle(ResizeHandle::Body);
+        let moving = (207.0, 300.0, 40.0, 40.0);
+        assert_eq!(guides().snap_offset(moving, body, &[TARGET], 1.0), (0.0, 0.0));
+        assert!(guides().collect(moving, body, &[TARGET], 1.0).is_empty());
+
+        // 吸附距离按屏幕像素计算，放大后世界坐标中的距离变小
+        let moving = (204.0, 300.0, 40.0, 40.0);
+        assert_eq!(guides().snap_offset(moving, body, &[TARGET], 1.0), (-4.0, 0.0));
+        assert_eq!(guides().snap_offset(moving, body, &[TARGET], 2.0), (0.0, 0.0));
+
+        let disabled = RgSmartGuides { enabled: false, ..guides() };
+        assert_eq!(disabled.snap_offset(moving, body, &[TARGET], 1.0), (0.0, 0.0));
+    }
+
+    #[test]
+    fn resize_snaps_only_moving_edges_and_respects_min_size() {
+        // 拖动右下角时只有右边和下边参与吸附，左上角保持不动
+        let edges = RgGuideEdges::for_handle(ResizeHandle::BottomRight);
+        let moving = (0.0, 0.0, 97.0, 146.0);
+        let offset = guides().snap_offset(moving, edges, &[TARGET], 1.0);
+        assert_eq!(offset, (3.0, 4.0));
+        assert_eq!(edges.resize(moving, offset), Some((0.0, 0.0, 100.0, 150.0)));
+
+        // 拖动左边时移动 x 并反向调整宽度
+        let edges = RgGuideEdges::for_handle(ResizeHandle::Left);
+        let moving = (95.0, 0.0, 50.0, 20.0);
+        let offset = guides().snap_offset(moving, edges, &[TARGET], 1.0);
+        assert_eq!(offset, (5.0, 0.0));
+        assert_eq!(edges.resize(moving, offset), Some((100.0, 0.0, 45.0, 20.0)));
+
+        // 吸附后小于最小尺寸时放弃吸附
+        let moving = (96.0, 0.0, 12.0, 20.0);
+        let offset = guides().snap_offset(moving, edges, &[TARGET], 1.0);
+        assert_eq!(offset, (4.0, 0.0));
+        assert_eq!(edges.resize(moving, offset), None);
+        assert_eq!(edges.resize(moving, (4.0 - (12.0 - MIN_NODE_SIZE), 0.0)), Some((98.0, 0.0, MIN_NODE_SIZE, 20.0)));
+    }
+}
@@ -0,0 +1,109 @@
+use crate::*;
+
+//************************************************************************** RgHistory **********************************************************************************//
+/// 节点几何数据 (x, y, width, height)，与 `RgRect::initial_drag_data` 保持一致
+pub type RgGeometry = (f32, f32, f32, f32);
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RgCommand {
+    Move { changes: Vec<(u64, RgGeometry, RgGeometry)> },
+    Resize { changes: Vec<(u64, RgGeometry, RgGeometry)> },
+    /// (节点 id, 旧角度, 新角度)
+    Rotate { changes: Vec<(u64, f32, f32)> },
+    /// (节点 id, 旧样式, 新样式)
+    Restyle { changes: Vec<(u64, RgStyle, RgStyle)> },
+    /// (节点 id, 旧标签, 新标签)
+    Relabel { changes: Vec<(u64, String, String)> },
+    Reorder { before: Vec<u64>, after: Vec<u64> },
+    Select { before: Vec<u64>, after: Vec<u64> },
+    // 节点快照中的 z_order 为节点在 RgEditor.nodes 中的下标
+    Add { nodes: Vec<RgNodeData> },
+    Remove { nodes: Vec<RgNodeData> },
+    // parent 为组所在的父组，member_parents 为成员原来的父组
+    Group { group: RgGroupData, parent: Option<u64>, member_parents: Vec<(RgGroupMember, Option<u64>)> },
+    Ungroup { group: RgGroupData, parent: Option<u64> },
+    AddConnectors { connectors: Vec<RgConnectorData> },
+    RemoveConnectors { connectors: Vec<RgConnectorData> },
+}
+
+/// 一次完整操作（例如一次鼠标按下到抬起）产生的全部命令，撤销/重做时作为整体处理
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RgTransaction {
+    pub commands: Vec<RgCommand>,
+}
+
+pub struct RgHistory {
+    undo_stack: Vec<RgTransaction>,
+    redo_stack: Vec<RgTransaction>,
+    limit: usize,
+}
+
+//************************************************************************** Trait **********************************************************************************//
+impl RgTransaction {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, command: RgCommand) {
+        self.commands.push(command);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+}
+
+impl RgHistory {
+    pub fn new(limit: usize) -> Self {
+        Self {
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
+            limit,
+        }
+    }
+
+    pub fn push(&mut self, transaction: RgTransaction) {
+        if transaction.is_empty() {
+            return;
+        }
+
+        self.undo_stack.push(transaction);
+        self.redo_stack.clear();
+
+        if self.undo_stack.len() > self.limit {
+            let overflow = self.undo_stack.len() - self.limit;
+            self.undo_stack.drain(0..overflow);
+        }
+    }
+
+    pub fn pop_undo(&mut self) -> Option<RgTransaction> {
+        let transaction = self.undo_stack.pop()?;
+        self.redo_stack.push(transaction.clone());
+        Some(transaction)
+    }
+
+    pub fn pop_redo(&mut self) -> Option<RgTransaction> {
+        let transaction = self.redo_stack.pop()?;
+        self.undo_stack.push(transaction.clone());
+        Some(transaction)
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo_stack.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo_stack.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+    }
+}
+
+impl Default for RgHistory {
+    fn default() -> Self {
+        Self::new(200)
+    }
+}
@@ -0,0 +1,173 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use gpui::*;
+use serde::{Deserialize, Serialize};
+
+//************************************************************************** RgImage **********************************************************************************//
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RgImageFormat {
+    Png,
+    Jpeg,
+    Svg,
+}
+
+/// 图片在节点内的缩放方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RgImageFit {
+    /// 完整显示在节点内，保持宽高比
+    #[default]
+    Fit,
+    /// 铺满节点，保持宽高比，超出部分裁掉
+    Fill,
+    /// 拉伸到节点大小
+    Stretch,
+}
+
+/// 图片来源：本地文件路径，或嵌入文档的文件内容（保存为 base64）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum RgImageSource {
+    Path(PathBuf),
+    Embedded(#[serde(with = "embedded_image")] Arc<Image>),
+}
+
+/// 图片节点的数据。图片不随节点旋转
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RgImage {
+    pub source: RgImageSource,
+    #[serde(default)]
+    pub fit: RgImageFit,
+    /// 调整大小时保持宽高比，按住 Shift 时反过来自由调整
+    #[serde(default = "default_lock_aspect_ratio")]
+    pub lock_aspect_ratio: bool,
+}
+
+//************************************************************************** Trait **********************************************************************************//
+impl RgImageFormat {
+    /// 按扩展名识别格式，只支持 PNG、JPEG 和 SVG
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "png" => Some(RgImageFormat::Png),
+            "jpg" | "jpeg" => Some(RgImageFormat::Jpeg),
+            "svg" => Some(RgImageFormat::Svg),
+            _ => None,
+        }
+    }
+
+    fn from_image_format(format: ImageFormat) -> Option<Self> {
+        match format {
+            ImageFormat::Png => Some(RgImageFormat::Png),
+            ImageFormat::Jpeg => Some(RgImageFormat::Jpeg),
+            ImageFormat::Svg => Some(RgImageFormat::Svg),
+            _ => None,
+        }
+    }
+
+    fn image_format(&self) -> ImageFormat {
+        match self {
+            RgImageFormat::Png => ImageFormat::Png,
+            RgImageFormat::Jpeg => ImageFormat::Jpeg,
+            RgImageFormat::Svg => ImageFormat::Svg,
+        }
+    }
+}
+
+// gpui 的 Image 相等比较包含每次创建都不同的 id，嵌入图片只比较格式和内容
+impl PartialEq for RgImageSource {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (RgImageSource::Path(a), RgImageSource::Path(b)) => a == b,
+            (RgImageSource::Embedded(a), RgImageSource::Embedded(b)) => a.format == b.format && a.bytes == b.bytes,
+            _ => false,
+        }
+    }
+}
+
+impl RgImageFit {
+    pub fn object_fit(&self) -> ObjectFit {
+        match self {
+            RgImageFit::Fit => ObjectFit::Contain,
+            RgImageFit::Fill => ObjectFit::Cover,
+            RgImageFit::Stretch => ObjectFit::Fill,
+        }
+    }
+}
+
+impl RgImage {
+    /// 引用本地文件，文档中只保存路径。扩展名不是 PNG、JPEG 或 SVG 时返回 None
+    pub fn from_path(path: impl Into<PathBuf>) -> Option<Self> {
+        let path = path.into();
+        RgImageFormat::from_path(&path)?;
+        Some(Self::new(RgImageSource::Path(path)))
+    }
+
+    pub fn from_bytes(format: RgImageFormat, bytes: Vec<u8>) -> Self {
+        Self::new(RgImageSource::Embedded(Arc::new(Image::from_bytes(format.image_format(), bytes))))
+    }
+
+    /// 读取本地文件并嵌入文档
+    pub fn embed_file(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let path = path.as_ref();
+        let format = RgImageFormat::from_path(path)
+            .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::InvalidInput, "unsupported image format"))?;
+        Ok(Self::from_bytes(format, std::fs::read(path)?))
+    }
+
+    fn new(source: RgImageSource) -> Self {
+        Self {
+            source,
+            fit: RgImageFit::default(),
+            lock_aspect_ratio: default_lock_aspect_ratio(),
+        }
+    }
+
+    /// 将路径引用转换为嵌入的文件内容，已嵌入时原样返回
+    pub fn embedded(&self) -> std::io::Result<Self> {
+        match &self.source {
+            RgImageSource::Path(path) => Ok(Self {
+                fit: self.fit,
+                lock_aspect_ratio: self.lock_aspect_ratio,
+                ..Self::embed_file(path)?
+            }),
+            RgImageSource::Embedded(_) => Ok(self.clone()),
+        }
+    }
+
+    pub fn image_source(&self) -> ImageSource {
+        match &self.source {
+            RgImageSource::Path(path) => path.clone().into(),
+            RgImageSource::Embedded(image) => image.clone().into(),
+        }
+    }
+}
+
+fn default_lock_aspect_ratio() -> bool {
+    true
+}
+
+// 嵌入图片的序列化格式：{ "format": "Png", "data": "<base64>" }
+mod embedded_image {
+    use super::*;
+    use serde::{de::Error, Deserializer, Serializer};
+
+    #[derive(Serialize, Deserialize)]
+    struct RgEmbeddedImage {
+        format: RgImageFormat,
+        data: String,
+    }
+
+    pub fn serialize<S: Serializer>(image: &Arc<Image>, serializer: S) -> Result<S::Ok, S::Error> {
+        let format = RgImageFormat::from_image_format(image.format)
+            .ok_or_else(|| serde::ser::Error::custom("unsupported image format"))?;
+        RgEmbeddedImage { format, data: BASE64.encode(&image.bytes) }.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Arc<Image>, D::Error> {
+        let embedded = RgEmbeddedImage::deserialize(deserializer)?;
+        let bytes = BASE64.decode(&embedded.data).map_err(|error| D::Error::custom(format!("invalid base64 image data: {error}")))?;
+        Ok(Arc::new(Image::from_bytes(embedded.format.image_format(), bytes)))
+    }
+}
@@ -0,0 +1,551 @@
+use std::collections::HashMap;
+
+use crate::*;
+
+//************************************************************************** RgLayout **********************************************************************************//
+const MAX_OVERLAP_PASSES: usize = 50;
+const MAX_SPREAD_ROUNDS: usize = 30;
+
+/// 自动布局算法。items 为参与布局的单元（单个节点或整个组）的世界坐标包围盒，
+/// edges 为单元下标之间的连线。返回每个单元新的左上角位置，顺序与 items 相同
+pub trait RgLayout {
+    fn layout(&self, items: &[RgGeometry], edges: &[(usize, usize)]) -> Vec<(f32, f32)>;
+}
+
+/// 按阅读顺序（先上后下、先左后右）排入等大的网格单元
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RgGridLayout {
+    /// 列数，None 时取接近正方形的列数
+    pub columns: Option<usize>,
+    pub spacing: f32,
+}
+
+/// 货架装箱：按高度从高到低逐行排放，行宽超过 max_width 时换行，结果没有重叠
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RgShelfLayout {
+    /// 最大行宽（世界坐标），None 时按总面积取接近正方形的宽度
+    pub max_width: Option<f32>,
+    pub spacing: f32,
+}
+
+/// 力导向布局：连线两端相互吸引，邻近单元相互排斥，最后消除剩余的重叠
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RgForceLayout {
+    pub iterations: usize,
+    /// 连线两端包围盒之间的理想距离
+    pub spring_length: f32,
+    /// 消除重叠时保留的最小间距
+    pub spacing: f32,
+}
+
+/// 对齐方式。HorizontalCenter 为水平方向居中（中心 x 对齐），VerticalCenter 为竖直方向居中（中心 y 对齐）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RgAlignment {
+    Left,
+    HorizontalCenter,
+    Right,
+    Top,
+    VerticalCenter,
+    Bottom,
+}
+
+/// 均匀分布方式：按间距使相邻单元之间的空隙相等，按中心使相邻单元的中心距离相等
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RgDistribution {
+    HorizontalSpacing,
+    VerticalSpacing,
+    HorizontalCenters,
+    VerticalCenters,
+}
+
+//************************************************************************** Trait **********************************************************************************//
+impl RgAlignment {
+    /// 将每个单元与 reference 对齐所需的偏移 (dx, dy)
+    pub fn offsets(&self, items: &[RgGeometry], reference: RgGeometry) -> Vec<(f32, f32)> {
+        let (rx, ry, rw, rh) = reference;
+        items.iter()
+            .map(|(x, y, width, height)| match self {
+                RgAlignment::Left => (rx - x, 0.0),
+                RgAlignment::HorizontalCenter => (rx + rw / 2.0 - (x + width / 2.0), 0.0),
+                RgAlignment::Right => (rx + rw - (x + width), 0.0),
+                RgAlignment::Top => (0.0, ry - y),
+                RgAlignment::VerticalCenter => (0.0, ry + rh / 2.0 - (y + height / 2.0)),
+                RgAlignment::Bottom => (0.0, ry + rh - (y + height)),
+            })
+            .collect()
+    }
+}
+
+impl RgDistribution {
+    /// 均匀分布所需的偏移 (dx, dy)。按当前位置排序，首尾两个单元保持不动，少于 3 个单元时不移动
+    pub fn offsets(&self, items: &[RgGeometry]) -> Vec<(f32, f32)> {
+        let mut offsets = vec![(0.0, 0.0); items.len()];
+        if items.len() < 3 {
+            return offsets;
+        }
+
+        let horizontal = matches!(self, RgDistribution::HorizontalSpacing | RgDistribution::HorizontalCenters);
+        // 沿分布方向的 (起点, 长度)
+        let span = |item: &RgGeometry| if horizontal { (item.0, item.2) } else { (item.1, item.3) };
+
+        let mut order: Vec<usize> = (0..items.len()).collect();
+        order.sort_by(|a, b| {
+            let (start_a, size_a) = span(&items[*a]);
+            let (start_b, size_b) = span(&items[*b]);
+            (start_a + size_a / 2.0).total_cmp(&(start_b + size_b / 2.0)).then(a.cmp(b))
+        });
+
+        let (first_start, first_size) = span(&items[order[0]]);
+        let (last_start, last_size) = span(&items[order[order.len() - 1]]);
+        let steps = (order.len() - 1) as f32;
+
+        let targets: Vec<f32> = match self {
+            RgDistribution::HorizontalSpacing | RgDistribution::VerticalSpacing => {
+                // 首尾之间的总空隙平均分配，空隙可能为负（单元总长超过首尾距离时相互重叠）
+                let total: f32 = order.iter().map(|index| span(&items[*index]).1).sum();
+                let gap = (last_start + last_size - first_start - total) / steps;
+                let mut position = first_start;
+                order.iter()
+                    .map(|index| {
+                        let start = position;
+                        position += span(&items[*index]).1 + gap;
+                        start
+                    })
+                    .collect()
+            }
+            RgDistribution::HorizontalCenters | RgDistribution::VerticalCenters => {
+                let first_center = first_start + first_size / 2.0;
+                let step = (last_start + last_size / 2.0 - first_center) / steps;
+                order.iter()
+                    .enumerate()
+                    .map(|(slot, index)| first_center + step * slot as f32 - span(&items[*index]).1 / 2.0)
+                    .collect()
+            }
+        };
+
+        for (index, target) in order.iter().zip(targets) {
+            let delta = target - span(&items[*index]).0;
+            offsets[*index] = if horizontal { (delta, 0.0) } else { (0.0, delta) };
+        }
+        // 首尾保持不动，避免浮点误差带来的微小移动
+        offsets[order[0]] = (0.0, 0.0);
+        offsets[order[order.len() - 1]] = (0.0, 0.0);
+        offsets
+    }
+}
+
+impl RgLayout for RgGridLayout {
+    fn layout(&self, items: &[RgGeometry], _edges: &[(usize, usize)]) -> Vec<(f32, f32)> {
+        if items.is_empty() {
+            return Vec::new();
+        }
+
+        let columns = self.columns
+            .unwrap_or_else(|| (items.len() as f32).sqrt().ceil() as usize)
+            .max(1);
+        let cell_width = items.iter().map(|item| item.2).fold(0.0, f32::max) + self.spacing;
+        let cell_height = items.iter().map(|item| item.3).fold(0.0, f32::max) + self.spacing;
+
+        let mut positions = vec![(0.0, 0.0); items.len()];
+        for (slot, index) in reading_order(items).into_iter().enumerate() {
+            let (_, _, width, height) = items[index];
+            let column = (slot % columns) as f32;
+            let row = (slot / columns) as f32;
+            // 在单元格内居中
+            positions[index] = (
+                column * cell_width + (cell_width - self.spacing - width) / 2.0,
+                row * cell_height + (cell_height - self.spacing - height) / 2.0,
+            );
+        }
+        positions
+    }
+}
+
+impl Default for RgGridLayout {
+    fn default() -> Self {
+        Self {
+            columns: None,
+            spacing: 20.0,
+        }
+    }
+}
+
+impl RgLayout for RgShelfLayout {
+    fn layout(&self, items: &[RgGeometry], _edges: &[(usize, usize)]) -> Vec<(f32, f32)> {
+        if items.is_empty() {
+            return Vec::new();
+        }
+
+        let widest = items.iter().map(|item| item.2).fold(0.0, f32::max);
+        let area: f32 = items.iter().map(|item| (item.2 + self.spacing) * (item.3 + self.spacing)).sum();
+        let max_width = self.max_width.unwrap_or_else(|| area.sqrt()).max(widest);
+
+        let mut order: Vec<usize> = (0..items.len()).collect();
+        order.sort_by(|a, b| items[*b].3.total_cmp(&items[*a].3).then(a.cmp(b)));
+
+        let mut positions = vec![(0.0, 0.0); items.len()];
+        let (mut x, mut y, mut shelf_height) = (0.0, 0.0, 0.0);
+        for index in order {
+            let (_, _, width, height) = items[index];
+            if x > 0.0 && x + width > max_width {
+                x = 0.0;
+                y += shelf_height + self.spacing;
+                shelf_height = 0.0;
+            }
+            positions[index] = (x, y);
+            x += width + self.spacing;
+            shelf_height = f32::max(shelf_height, height);
+        }
+        positions
+    }
+}
+
+impl Default for RgShelfLayout {
+    fn default() -> Self {
+        Self {
+            max_width: None,
+            spacing: 20.0,
+        }
+    }
+}
+
+impl RgLayout for RgForceLayout {
+    fn layout(&self, items: &[RgGeometry], edges: &[(usize, usize)]) -> Vec<(f32, f32)> {
+        let count = items.len();
+        if count == 0 {
+            return Vec::new();
+        }
+
+        let k = self.spring_length.max(1.0);
+        let radii: Vec<f32> = items.iter().map(|item| item.2.max(item.3) / 2.0).collect();
+        let max_radius = radii.iter().copied().fold(0.0, f32::max);
+        // 超过该距离的单元之间不再排斥，用网格只比较邻近的单元
+        let cutoff = k * 3.0 + max_radius * 2.0;
+
+        let mut centers: Vec<(f32, f32)> = items.iter()
+            .map(|(x, y, width, height)| (x + width / 2.0, y + height / 2.0))
+            .collect();
+        let mut temperature = k * 2.0;
+        let cooling = temperature / self.iterations.max(1) as f32;
+
+        for _ in 0..self.iterations {
+            let mut displacement = vec![(0.0_f32, 0.0_f32); count];
+
+            //****************************** 1.排斥 ****************************//
+            let cells = build_cells(&centers, cutoff);
+            for (i, center) in centers.iter().enumerate() {
+                for j in neighbors(&cells, *center, cutoff) {
+                    if j <= i {
+                        continue;
+                    }
+                    let (dx, dy, distance) = separation(*center, centers[j], i, j);
+                    if distance >= cutoff {
+                        continue;
+                    }
+                    // 以包围圆之间的距离计算，大单元之间排斥更强
+                    let gap = (distance - radii[i] - radii[j]).max(1.0);
+                    let force = k * k / gap;
+                    let (fx, fy) = (dx / distance * force, dy / distance * force);
+                    displacement[i].0 += fx;
+                    displacement[i].1 += fy;
+                    displacement[j].0 -= fx;
+                    displacement[j].1 -= fy;
+                }
+            }
+
+            //****************************** 2.吸引 ****************************//
+            for (a, b) in edges {
+                let (a, b) = (*a, *b);
+                if a == b || a >= count || b >= count {
+                    continue;
+                }
+                let (dx, dy, distance) = separation(centers[a], centers[b], a, b);
+                let gap = (distance - radii[a] - radii[b]).max(0.0);
+                let force = gap * gap / k;
+                let (fx, fy) = (dx / distance * force, dy / distance * force);
+                displacement[a].0 -= fx;
+                displacement[a].1 -= fy;
+                displacement[b].0 += fx;
+                displacement[b].1 += fy;
+            }
+
+            //****************************** 3.移动 ****************************//
+            // 轻微的向心力，避免不相连的部分越飘越远
+            let centroid = (
+                centers.iter().map(|c| c.0).sum::<f32>() / count as f32,
+                centers.iter().map(|c| c.1).sum::<f32>() / count as f32,
+            );
+            for (center, (mut dx, mut dy)) in centers.iter_mut().zip(displacement) {
+                dx += (centroid.0 - center.0) * 0.002;
+                dy += (centroid.1 - center.1) * 0.002;
+                let length = (dx * dx + dy * dy).sqrt();
+                if length > 0.0 {
+                    let step = length.min(temperature);
+                    center.0 += dx / length * step;
+                    center.1 += dy / length * step;
+                }
+            }
+            temperature = (temperature - cooling).max(k * 0.05);
+        }
+
+        let mut positions: Vec<(f32, f32)> = centers.iter()
+            .zip(items)
+            .map(|(center, (_, _, width, height))| (center.0 - width / 2.0, center.1 - height / 2.0))
+            .collect();
+        // 局部推开仍无法消除时（单元过于密集），以中心为基准整体放大后重试
+        for _ in 0..MAX_SPREAD_ROUNDS {
+            if remove_overlaps(&mut positions, items, self.spacing) {
+                break;
+            }
+            spread(&mut positions, items, 1.1);
+        }
+        positions
+    }
+}
+
+impl Default for RgForceLayout {
+    fn default() -> Self {
+        Self {
+            iterations: 200,
+            spring_length: 80.0,
+            spacing: 20.0,
+        }
+    }
+}
+
+// 按左上角先 y 后 x 排序的下标
+fn reading_order(items: &[RgGeometry]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..items.len()).collect();
+    order.sort_by(|a, b| {
+        items[*a].1.total_cmp(&items[*b].1)
+            .then(items[*a].0.total_cmp(&items[*b].0))
+            .then(a.cmp(b))
+    });
+    order
+}
+
+// a 指向 b 的向量和距离，两点重合时按下标给出固定方向
+fn separation(a: (f32, f32), b: (f32, f32), i: usize, j: usize) -> (f32, f32, f32) {
+    let (dx, dy) = (a.0 - b.0, a.1 - b.1);
+    let distance = (dx * dx + dy * dy).sqrt();
+    if distance > 0.01 {
+        return (dx, dy, distance);
+    }
+    let angle = (i * 31 + j * 17) as f32;
+    (angle.cos() * 0.01, angle.sin() * 0.01, 0.01)
+}
+
+fn build_cells(centers: &[(f32, f32)], cell_size: f32) -> HashMap<(i32, i32), Vec<usize>> {
+    let mut cells: HashMap<(i32, i32), Vec<usize>> = HashMap::new();
+    for (index, center) in centers.iter().enumerate() {
+        cells.entry(cell_of(*center, cell_size)).or_default().push(index);
+    }
+    cells
+}
+
+fn cell_of(point: (f32, f32), cell_size: f32) -> (i32, i32) {
+    ((point.0 / cell_size).floor() as i32, (point.1 / cell_size).floor() as i32)
+}
+
+fn neighbors<'a>(cells: &'a HashMap<(i32, i32), Vec<usize>>, point: (f32, f32), cell_size: f32) -> impl Iterator<Item = usize> + 'a {
+    let (col, row) = cell_of(point, cell_size);
+    (-1..=1)
+        .flat_map(move |dx| (-1..=1).map(move |dy| (col + dx, row + dy)))
+        .filter_map(|cell| cells.get(&cell))
+        .flatten()
+        .copied()
+}
+
+// 反复把重叠的单元沿重叠较小的方向推开，返回是否已经没有重叠
+fn remove_overlaps(positions: &mut [(f32, f32)], items: &[RgGeometry], spacing: f32) -> bool {
+    let cell_size = items.iter().map(|item| item.2.max(item.3)).fold(0.0, f32::max) + spacing;
+    if cell_size <= 0.0 {
+        return true;
+    }
+
+    for _ in 0..MAX_OVERLAP_PASSES {
+        let corners: Vec<(f32, f32)> = positions.to_vec();
+        let cells = build_cells(&corners, cell_size);
+        let mut moved = false;
+
+        for i in 0..positions.len() {
+            for j in neighbors(&cells, corners[i], cell_size) {
+                if j <= i {
+                    continue;
+                }
+                let (a, b) = (positions[i], positions[j]);
+                let overlap_x = (a.0 + items[i].2 + spacing).min(b.0 + items[j].2 + spacing) - a.0.max(b.0);
+                let overlap_y = (a.1 + items[i].3 + spacing).min(b.1 + items[j].3 + spacing) - a.1.max(b.1);
+                if overlap_x <= 0.0 || overlap_y <= 0.0 {
+                    continue;
+                }
+
+                let center_a = (a.0 + items[i].2 / 2.0, a.1 + items[i].3 / 2.0);
+                let center_b = (b.0 + items[j].2 / 2.0, b.1 + items[j].3 / 2.0);
+                let (dx, dy, _) = separation(center_b, center_a, j, i);
+                if overlap_x < overlap_y {
+                    let push = overlap_x / 2.0 * dx.signum();
+                    positions[i].0 -= push;
+                    positions[j].0 += push;
+                } else {
+                    let push = overlap_y / 2.0 * dy.signum();
+                    positions[i].1 -= push;
+                    positions[j].1 += push;
+                }
+                moved = true;
+            }
+        }
+
+        if !moved {
+            return true;
+        }
+    }
+    false
+}
+
+// 单元中心相对整体中心按 factor 放大，单元大小不变
+fn spread(positions: &mut [(f32, f32)], items: &[RgGeometry], factor: f32) {
+    let count = positions.len() as f32;
+    let centers: Vec<(f32, f32)> = positions.iter()
+        .zip(items)
+        .map(|(position, item)| (position.0 + item.2 / 2.0, position.1 + item.3 / 2.0))
+        .collect();
+    let centroid = (
+        centers.iter().map(|c| c.0).sum::<f32>() / count,
+        centers.iter().map(|c| c.1).sum::<f32>() / count,
+    );
+
+    for ((position, item), center) in positions.iter_mut().zip(items).zip(centers) {
+        position.0 = centroid.0 + (center.0 - centroid.0) * factor - item.2 / 2.0;
+        position.1 = centroid.1 + (center.1 - centroid.1) * factor - item.3 / 2.0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    use super::*;
+
+    fn random_items(count: usize, seed: u64) -> Vec<RgGeometry> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        (0..count)
+            .map(|_| {
+                (
+                    rng.gen_range(-500.0..500.0),
+                    rng.gen_range(-500.0..500.0),
+                    rng.gen_range(20.0..200.0),
+                    rng.gen_range(20.0..150.0),
+                )
+            })
+            .collect()
+    }
+
+    fn placed(items: &[RgGeometry], positions: &[(f32, f32)]) -> Vec<RgGeometry> {
+        assert_eq!(items.len(), positions.len());
+        items.iter().zip(positions).map(|((_, _, width, height), (x, y))| (*x, *y, *width, *height)).collect()
+    }
+
+    // 允许浮点误差范围内的贴边
+    fn assert_no_overlap(rects: &[RgGeometry]) {
+        for (i, a) in rects.iter().enumerate() {
+            for b in &rects[i + 1..] {
+                let overlap = a.0 + 0.01 < b.0 + b.2 && b.0 + 0.01 < a.0 + a.2 && a.1 + 0.01 < b.1 + b.3 && b.1 + 0.01 < a.1 + a.3;
+                assert!(!overlap, "{a:?} overlaps {b:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn grid_layout_has_no_overlap() {
+        let items = random_items(40, 1);
+        for columns in [None, Some(1), Some(7)] {
+            let positions = RgGridLayout { columns, spacing: 10.0 }.layout(&items, &[]);
+            assert_no_overlap(&placed(&items, &positions));
+        }
+    }
+
+    #[test]
+    fn shelf_layout_has_no_overlap() {
+        let items = random_items(40, 2);
+        for max_width in [None, Some(100.0), Some(600.0)] {
+            let positions = RgShelfLayout { max_width, spacing: 10.0 }.layout(&items, &[]);
+            assert_no_overlap(&placed(&items, &positions));
+        }
+    }
+
+    #[test]
+    fn force_layout_separates_overlapping_items() {
+        // 所有单元叠在同一个位置
+        let items = vec![(0.0, 0.0, 100.0, 60.0); 12];
+        let edges: Vec<(usize, usize)> = (1..items.len()).map(|index| (0, index)).collect();
+        let positions = RgForceLayout::default().layout(&items, &edges);
+        assert!(positions.iter().all(|(x, y)| x.is_finite() && y.is_finite()));
+        assert_no_overlap(&placed(&items, &positions));
+
+        let items = random_items(60, 3);
+        let positions = RgForceLayout::default().layout(&items, &[(0, 1), (1, 2), (2, 2), (5, 99)]);
+        assert_no_overlap(&placed(&items, &positions));
+
+        assert!(RgForceLayout::default().layout(&[], &[]).is_empty());
+    }
+
+    #[test]
+    fn distribution_keeps_ends_and_spaces_evenly() {
+        let items = vec![(0.0, 0.0, 50.0, 20.0), (300.0, 40.0, 20.0, 30.0), (70.0, 10.0, 40.0, 10.0), (160.0, 0.0, 100.0, 50.0)];
+        let moved = |offsets: Vec<(f32, f32)>| -> Vec<RgGeometry> {
+            items.iter().zip(offsets).map(|((x, y, width, height), (dx, dy))| (x + dx, y + dy, *width, *height)).collect()
+        };
+
+        let offsets = RgDistribution::HorizontalSpacing.offsets(&items);
+        assert_eq!(offsets[0], (0.0, 0.0));
+        assert_eq!(offsets[1], (0.0, 0.0));
+        assert!(offsets.iter().all(|(_, dy)| *dy == 0.0));
+        let mut rects = moved(offsets);
+        rects.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let gaps: Vec<f32> = rects.windows(2).map(|pair| pair[1].0 - (pair[0].0 + pair[0].2)).collect();
+        assert!(gaps.iter().all(|gap| (gap - gaps[0]).abs() < 0.01), "{gaps:?}");
+
+        let offsets = RgDistribution::VerticalCenters.offsets(&items);
+        let mut centers: Vec<f32> = moved(offsets.clone()).iter().map(|(_, y, _, height)| y + height / 2.0).collect();
+        centers.sort_by(f32::total_cmp);
+        let steps: Vec<f32> = centers.windows(2).map(|pair| pair[1] - pair[0]).collect();
+        assert!(steps.iter().all(|step| (step - steps[0]).abs() < 0.01), "{steps:?}");
+        // 中心最上和最下的单元不动
+        assert_eq!(offsets[0], (0.0, 0.0));
+        assert_eq!(offsets[1], (0.0, 0.0));
+
+        assert_eq!(RgDistribution::HorizontalCenters.offsets(&items[..2]), vec![(0.0, 0.0); 2]);
+    }
+
+    #[test]
+    fn alignment_keeps_reference_in_place() {
+        let items = random_items(10, 4);
+        let reference = items[3];
+        for alignment in [
+            RgAlignment::Left,
+            RgAlignment::HorizontalCenter,
+            RgAlignment::Right,
+            RgAlignment::Top,
+            RgAlignment::VerticalCenter,
+            RgAlignment::Bottom,
+        ] {
+            let offsets = alignment.offsets(&items, reference);
+            assert_eq!(offsets[3], (0.0, 0.0), "{alignment:?}");
+
+            let aligned = |(x, y, width, height): RgGeometry, (dx, dy): (f32, f32)| match alignment {
+                RgAlignment::Left => x + dx,
+                RgAlignment::HorizontalCenter => x + dx + width / 2.0,
+                RgAlignment::Right => x + dx + width,
+                RgAlignment::Top => y + dy,
+                RgAlignment::VerticalCenter => y + dy + height / 2.0,
+                RgAlignment::Bottom => y + dy + height,
+            };
+            let target = aligned(reference, (0.0, 0.0));
+            for (item, offset) in items.iter().zip(&offsets) {
+                assert!((aligned(*item, *offset) - target).abs() < 0.01, "{alignment:?}");
+            }
+        }
+    }
+}
@@ -0,0 +1,198 @@
+use std::collections::BTreeMap;
+
+use gpui::*;
+use crate::*;
+
+//************************************************************************** RgMinimap **********************************************************************************//
+// 缩略图内容与面板边缘的留白（屏幕像素）
+const MINIMAP_PADDING: f32 = 8.0;
+// 缩略图中节点的最小显示尺寸（屏幕像素），太小的节点也保持可见
+const MIN_NODE_PIXELS: f32 = 1.0;
+// 节点范围较长边划分的格子数，小于一个格子的节点合并为所在格子绘制
+const MINIMAP_CELLS: f32 = 256.0;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RgMinimapCorner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    #[default]
+    BottomRight,
+}
+
+/// 画布角落的缩略图，显示所有节点、内容区域和当前视口，点击或拖动时平移视图
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RgMinimap {
+    pub visible: bool,
+    pub corner: RgMinimapCorner,
+    /// 面板宽高（屏幕像素）
+    pub width: f32,
+    pub height: f32,
+    /// 面板与画布边缘的距离（屏幕像素）
+    pub margin: f32,
+}
+
+/// 世界坐标到缩略图面板坐标（相对面板左上角）的映射
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RgMinimapTransform {
+    pub scale: f32,
+    /// 世界坐标原点在面板中的位置
+    pub offset: (f32, f32),
+}
+
+//************************************************************************** Trait **********************************************************************************//
+impl RgMinimap {
+    /// 面板在画布中的位置和大小（相对画布左上角）
+    pub fn panel_bounds(&self, canvas_size: Size<Pixels>) -> Bounds<Pixels> {
+        let canvas_width = canvas_size.width.to_f64() as f32;
+        let canvas_height = canvas_size.height.to_f64() as f32;
+        let width = self.width.min(canvas_width - self.margin * 2.0).max(0.0);
+        let height = self.height.min(canvas_height - self.margin * 2.0).max(0.0);
+
+        let (x, y) = match self.corner {
+            RgMinimapCorner::TopLeft => (self.margin, self.margin),
+            RgMinimapCorner::TopRight => (canvas_width - self.margin - width, self.margin),
+            RgMinimapCorner::BottomLeft => (self.margin, canvas_height - self.margin - height),
+            RgMinimapCorner::BottomRight => (canvas_width - self.margin - width, canvas_height - self.margin - height),
+        };
+        Bounds::new(point(px(x), px(y)), size(px(width), px(height)))
+    }
+
+    /// 将 regions（世界坐标）的并集等比缩放后居中放进大小为 panel_size 的面板
+    pub fn transform(&self, panel_size: Size<Pixels>, regions: impl IntoIterator<Item = RgGeometry>) -> RgMinimapTransform {
+        let (min_x, min_y, max_x, max_y) = regions.into_iter().fold(
+            (f32::INFINITY, f32::INFINITY, f32::NEG_INFINITY, f32::NEG_INFINITY),
+            |(a, b, c, d), (x, y, width, height)| (a.min(x), b.min(y), c.max(x + width), d.max(y + height)),
+        );
+        if min_x > max_x || min_y > max_y {
+            return RgMinimapTransform { scale: 1.0, offset: (MINIMAP_PADDING, MINIMAP_PADDING) };
+        }
+
+        let available_width = (panel_size.width.to_f64() as f32 - MINIMAP_PADDING * 2.0).max(1.0);
+        let available_height = (panel_size.height.to_f64() as f32 - MINIMAP_PADDING * 2.0).max(1.0);
+        let world_width = (max_x - min_x).max(f32::EPSILON);
+        let world_height = (max_y - min_y).max(f32::EPSILON);
+        let scale = (available_width / world_width).min(available_height / world_height);
+
+        RgMinimapTransform {
+            scale,
+            offset: (
+                MINIMAP_PADDING + (available_width - world_width * scale) / 2.0 - min_x * scale,
+                MINIMAP_PADDING + (available_height - world_height * scale) / 2.0 - min_y * scale,
+            ),
+        }
+    }
+
+    /// 缩略图中绘制的 (包围盒, 是否选中)，世界坐标。extent 为所有节点的范围，
+    /// 小于一个格子的节点合并为所在格子，格子内有选中节点时显示为选中，绘制数量不随节点数量增长
+    pub fn merge_nodes(extent: RgGeometry, nodes: impl IntoIterator<Item = (RgGeometry, bool)>) -> Vec<(RgGeometry, bool)> {
+        let cell = (extent.2.max(extent.3) / MINIMAP_CELLS).max(f32::EPSILON);
+        let mut cells: BTreeMap<(i32, i32), bool> = BTreeMap::new();
+        let mut merged = Vec::new();
+
+        for (geometry, selected) in nodes {
+            let (x, y, width, height) = geometry;
+            if width >= cell || height >= cell {
+                merged.push((geometry, selected));
+                continue;
+            }
+            let col = ((x + width / 2.0 - extent.0) / cell).floor() as i32;
+            let row = ((y + height / 2.0 - extent.1) / cell).floor() as i32;
+            *cells.entry((col, row)).or_default() |= selected;
+        }
+
+        merged.extend(cells.into_iter().map(|((col, row), selected)| {
+            ((extent.0 + col as f32 * cell, extent.1 + row as f32 * cell, cell, cell), selected)
+        }));
+        merged
+    }
+
+    /// 绘制面板，bounds 为面板的窗口坐标，nodes 为 (包围盒, 是否选中)，均为世界坐标
+    pub fn paint(
+        &self,
+        bounds: Bounds<Pixels>,
+        transform: RgMinimapTransform,
+        nodes: &[(RgGeometry, bool)],
+        content_bounds: RgGeometry,
+        viewport: RgGeometry,
+        window: &mut Window,
+    ) {
+        if !self.visible {
+            return;
+        }
+
+        let to_bounds = |(x, y, width, height): RgGeometry| {
+            let (left, top) = transform.to_minimap((x, y));
+            Bounds::new(
+                bounds.origin + point(px(left), px(top)),
+                size(
+                    px((width * transform.scale).max(MIN_NODE_PIXELS)),
+                    px((height * transform.scale).max(MIN_NODE_PIXELS)),
+                ),
+            )
+        };
+
+        window.paint_quad(fill(bounds, rgba(0xffffffdd)));
+        window.with_content_mask(Some(ContentMask { bounds }), |window| {
+            window.paint_quad(outline(to_bounds(content_bounds), rgba(0x0000ff88), BorderStyle::Dashed));
+
+            for (geometry, selected) in nodes {
+                let color = if *selected { rgba(0x008affff) } else { rgba(0x505050aa) };
+                window.paint_quad(fill(to_bounds(*geometry), color));
+            }
+
+            let viewport = to_bounds(viewport);
+            window.paint_quad(fill(viewport, rgba(0x008aff18)));
+            window.paint_quad(outline(viewport, rgb(0x008aff), BorderStyle::Solid));
+        });
+        window.paint_quad(outline(bounds, rgba(0x00000044), BorderStyle::Solid));
+    }
+}
+
+impl Default for RgMinimap {
+    fn default() -> Self {
+        Self {
+            visible: true,
+            corner: RgMinimapCorner::BottomRight,
+            width: 200.0,
+            height: 150.0,
+            margin: 12.0,
+        }
+    }
+}
+
+impl RgMinimapTransform {
+    pub fn to_minimap(&self, (x, y): (f32, f32)) -> (f32, f32) {
+        (self.offset.0 + x * self.scale, self.offset.1 + y * self.scale)
+    }
+
+    pub fn to_world(&self, (x, y): (f32, f32)) -> (f32, f32) {
+        ((x - self.offset.0) / self.scale, (y - self.offset.1) / self.scale)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merge_nodes_bounds_small_nodes_by_cell() {
+        let extent = (0.0, 0.0, 2560.0, 1280.0);
+        // 格子边长 10，同一格子内的小节点合并，有选中节点时整个格子为选中
+        let nodes = vec![
+            ((1.0, 1.0, 2.0, 2.0), false),
+            ((5.0, 5.0, 2.0, 2.0), true),
+            ((21.0, 1.0, 2.0, 2.0), false),
+            ((100.0, 100.0, 40.0, 5.0), false),
+        ];
+        assert_eq!(RgMinimap::merge_nodes(extent, nodes), vec![
+            ((100.0, 100.0, 40.0, 5.0), false),
+            ((0.0, 0.0, 10.0, 10.0), true),
+            ((20.0, 0.0, 10.0, 10.0), false),
+        ]);
+
+        // 大量小节点合并后的数量不超过格子数
+        let many = (0..100_000).map(|i| (((i % 1000) as f32 * 2.56, (i / 1000) as f32 * 12.8, 1.0, 1.0), false));
+        assert!(RgMinimap::merge_nodes(extent, many).len() <= 256 * 128);
+    }
+}
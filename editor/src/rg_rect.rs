@@ -1,6 +1,18 @@
 use gpui::*;
+use crate::*;
 
 //************************************************************************** RgRect **********************************************************************************//
+// 旋转手柄到上边缘的距离（屏幕像素，随缩放放大）
+pub const ROTATE_HANDLE_DISTANCE: f32 = 24.0;
+const ROTATION_SNAP_DEGREES: f32 = 15.0;
+/// 调整大小时节点的最小宽高（世界坐标）
+pub const MIN_NODE_SIZE: f32 = 10.0;
+// 直线和手绘路径的点击检测范围（屏幕像素），另加上边框宽度的一半
+const LINE_HIT_TOLERANCE: f32 = 6.0;
+// 旋转或非矩形形状的虚线边框的线段和间隔长度（屏幕像素，随缩放放大）
+const DASH_LENGTH: f32 = 6.0;
+const DASH_GAP: f32 = 4.0;
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum ResizeHandle {
     TopLeft,
@@ -11,22 +23,47 @@ pub enum ResizeHandle {
     Bottom,
     BottomLeft,
     Left,
+    Rotate,
     Body,
     None,
 }
 
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct RgDragOptions {
+    /// 调整大小时边缘吸附的网格步长（世界坐标）。
+    /// 整体移动的吸附由 RgEditor 统一计算，以保持多选节点之间的相对位置
+    pub snap_step: Option<f32>,
+    /// 旋转时按 15° 吸附
+    pub snap_rotation: bool,
+    /// 调整大小时保持初始宽高比
+    pub keep_aspect_ratio: bool,
+    /// 调整大小时以初始中心对称缩放
+    pub from_center: bool,
+}
+
 pub struct RgRect {
     pub id: u64,
     pub x: f32,
     pub y: f32,
     pub width: f32,
     pub height: f32,
+    /// 绕中心顺时针旋转的角度（度）
+    pub rotation: f32,
+    pub shape: RgShape,
+    pub style: RgStyle,
+    pub label: String,
+    /// 标签正在原位编辑，此时由 RgTextEditor 绘制文字
+    pub is_editing_label: bool,
+    /// 图片节点显示的图片
+    pub image: Option<RgImage>,
     pub drag_offset: Option<(f32, f32)>,
     pub resize_handle: ResizeHandle,
     pub zoom: f32,
     pub pan: (f32, f32),
     pub selected: bool,
     pub initial_drag_data: Option<(f32, f32, f32, f32)>,
+    /// 旋转开始时的 (角度, 鼠标相对中心的方向角)
+    pub initial_rotation: Option<(f32, f32)>,
     pub is_dragging: bool,
     pub is_resizing: bool,
     pub current_mouse_position: Option<Point<Pixels>>,
@@ -42,12 +79,19 @@ impl RgRect {
             y,
             width,
             height,
+            rotation: 0.0,
+            shape: RgShape::Rectangle,
+            style: RgStyle::default(),
+            label: format!("Rect {}", id),
+            is_editing_label: false,
+            image: None,
             drag_offset: None,
             resize_handle: ResizeHandle::None,
             zoom: 1.0,
             pan: (0.0, 0.0),
             selected: false,
             initial_drag_data: None,
+            initial_rotation: None,
             is_dragging: false,
             is_resizing: false,
             current_mouse_position: None,
@@ -55,6 +99,42 @@ impl RgRect {
         }
     }
 
+    /// 图片节点，默认没有标签
+    pub fn new_image(id: u64, x: f32, y: f32, width: f32, height: f32, image: RgImage) -> Self {
+        let mut node = Self::new(id, x, y, width, height);
+        node.label = String::new();
+        node.image = Some(image);
+        node
+    }
+
+    pub fn from_node_data(data: &RgNodeData) -> Self {
+        let mut node = Self::new(data.id, data.x, data.y, data.width, data.height);
+        node.rotation = data.rotation;
+        node.shape = data.shape.clone();
+        node.style = data.style;
+        if let Some(label) = &data.label {
+            node.label = label.clone();
+        }
+        node.image = data.image.clone();
+        node
+    }
+
+    pub fn to_node_data(&self, z_order: usize) -> RgNodeData {
+        RgNodeData {
+            id: self.id,
+            x: self.x,
+            y: self.y,
+            width: self.width,
+            height: self.height,
+            rotation: self.rotation,
+            z_order,
+            shape: self.shape.clone(),
+            style: self.style,
+            label: Some(self.label.clone()),
+            image: self.image.clone(),
+        }
+    }
+
     pub fn screen_position(&self) -> (f32, f32) {
         (
             self.pan.0 + self.x * self.zoom,
@@ -80,11 +160,95 @@ impl RgRect {
         )
     }
 
+    pub fn screen_center(&self) -> (f32, f32) {
+        self.world_to_screen(self.x + self.width / 2.0, self.y + self.height / 2.0)
+    }
+
+    // 将屏幕坐标绕节点中心旋转 degrees 度
+    fn rotate_screen_point(&self, point: (f32, f32), degrees: f32) -> (f32, f32) {
+        if degrees == 0.0 {
+            return point;
+        }
+        rotate_point(point, self.screen_center(), degrees)
+    }
+
+    /// 旋转后的四个角（世界坐标），顺序为左上、右上、右下、左下
+    pub fn world_corners(&self) -> [(f32, f32); 4] {
+        let center = (self.x + self.width / 2.0, self.y + self.height / 2.0);
+
+        [
+            (self.x, self.y),
+            (self.x + self.width, self.y),
+            (self.x + self.width, self.y + self.height),
+            (self.x, self.y + self.height),
+        ]
+        .map(|corner| rotate_point(corner, center, self.rotation))
+    }
+
+    /// 形状实际使用的圆角半径（世界坐标）
+    pub fn corner_radius(&self) -> f32 {
+        self.shape.corner_radius(&self.style, self.width, self.height)
+    }
+
+    /// 形状的外轮廓（世界坐标，已旋转），不闭合的形状为折线
+    pub fn world_outline(&self) -> Vec<(f32, f32)> {
+        let center = (self.x + self.width / 2.0, self.y + self.height / 2.0);
+
+        self.shape.outline(self.width, self.height, self.corner_radius())
+            .into_iter()
+            .map(|(x, y)| rotate_point((self.x + x, self.y + y), center, self.rotation))
+            .collect()
+    }
+
+    // 未旋转的屏幕坐标是否命中形状
+    fn hit_shape(&self, screen_x: f32, screen_y: f32) -> bool {
+        let (node_screen_x, node_screen_y) = self.screen_position();
+        let local = ((screen_x - node_screen_x) / self.zoom, (screen_y - node_screen_y) / self.zoom);
+        let tolerance = LINE_HIT_TOLERANCE / self.zoom + self.style.stroke_width.max(0.0) / 2.0;
+
+        self.shape.contains(local, self.width, self.height, self.corner_radius(), tolerance)
+    }
+
+    /// 旋转后的轴对齐包围盒（世界坐标）
+    pub fn world_bounds(&self) -> (f32, f32, f32, f32) {
+        if self.rotation == 0.0 {
+            return (self.x, self.y, self.width, self.height);
+        }
+
+        let corners = self.world_corners();
+        let min_x = corners.iter().map(|c| c.0).fold(f32::INFINITY, f32::min);
+        let min_y = corners.iter().map(|c| c.1).fold(f32::INFINITY, f32::min);
+        let max_x = corners.iter().map(|c| c.0).fold(f32::NEG_INFINITY, f32::max);
+        let max_y = corners.iter().map(|c| c.1).fold(f32::NEG_INFINITY, f32::max);
+        (min_x, min_y, max_x - min_x, max_y - min_y)
+    }
+
+    /// 与轴对齐矩形（世界坐标）是否相交，使用分离轴检测
+    pub fn intersects_world_rect(&self, rect: (f32, f32, f32, f32)) -> bool {
+        let (x, y, width, height) = rect;
+        let rect_corners = [(x, y), (x + width, y), (x + width, y + height), (x, y + height)];
+        let corners = self.world_corners();
+        let (sin, cos) = self.rotation.to_radians().sin_cos();
+
+        let project = |points: &[(f32, f32)], axis: (f32, f32)| {
+            points.iter().fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), p| {
+                let d = p.0 * axis.0 + p.1 * axis.1;
+                (min.min(d), max.max(d))
+            })
+        };
+
+        [(1.0, 0.0), (0.0, 1.0), (cos, sin), (-sin, cos)].iter().all(|axis| {
+            let (min_a, max_a) = project(&corners, *axis);
+            let (min_b, max_b) = project(&rect_corners, *axis);
+            min_a < max_b && min_b < max_a
+        })
+    }
+
     pub fn get_handle_position(&self, handle: ResizeHandle) -> (f32, f32) {
         let (screen_x, screen_y) = self.screen_position();
         let (screen_width, screen_height) = self.screen_size();
 
-        match handle {
+        let position = match handle {
             ResizeHandle::TopLeft => (screen_x, screen_y),
             ResizeHandle::Top => (screen_x + screen_width / 2.0, screen_y),
             ResizeHandle::TopRight => (screen_x + screen_width, screen_y),
@@ -93,25 +257,26 @@ impl RgRect {
             ResizeHandle::Bottom => (screen_x + screen_width / 2.0, screen_y + screen_height),
             ResizeHandle::BottomLeft => (screen_x, screen_y + screen_height),
             ResizeHandle::Left => (screen_x, screen_y + screen_height / 2.0),
-            _ => (0.0, 0.0),
-        }
+            ResizeHandle::Rotate => (screen_x + screen_width / 2.0, screen_y - ROTATE_HANDLE_DISTANCE * self.zoom.max(1.0)),
+            _ => return (0.0, 0.0),
+        };
+
+        self.rotate_screen_point(position, self.rotation)
     }
 
     pub fn detect_handle_at(&self, position: Point<Pixels>) -> ResizeHandle {
-        let screen_x = position.x.to_f64() as f32;
-        let screen_y = position.y.to_f64() as f32;
+        // 转换到节点未旋转时的屏幕坐标系，手柄按轴对齐的包围盒检测，内部按形状检测
+        let (screen_x, screen_y) = self.rotate_screen_point(
+            (position.x.to_f64() as f32, position.y.to_f64() as f32),
+            -self.rotation,
+        );
 
         let (node_screen_x, node_screen_y) = self.screen_position();
         let (node_screen_width, node_screen_height) = self.screen_size();
 
-        // 如果节点未选中，只检查是否在矩形内部
+        // 如果节点未选中，只检查是否在形状内部
         if !self.selected {
-            let is_inside = screen_x >= node_screen_x
-                && screen_x <= node_screen_x + node_screen_width
-                && screen_y >= node_screen_y
-                && screen_y <= node_screen_y + node_screen_height;
-
-            return if is_inside {
+            return if self.hit_shape(screen_x, screen_y) {
                 ResizeHandle::Body
             } else {
                 ResizeHandle::None
@@ -122,12 +287,7 @@ impl RgRect {
         // 这里需要从外部获取是否是多选状态，但我们可以通过检查 show_handles 来判断
         // 在 RgEditor 中，多选时会将 show_handles 设置为 false
         if !self.show_handles {
-            let is_inside = screen_x >= node_screen_x
-                && screen_x <= node_screen_x + node_screen_width
-                && screen_y >= node_screen_y
-                && screen_y <= node_screen_y + node_screen_height;
-
-            return if is_inside {
+            return if self.hit_shape(screen_x, screen_y) {
                 ResizeHandle::Body
             } else {
                 ResizeHandle::None
@@ -149,8 +309,13 @@ impl RgRect {
             (ResizeHandle::Left, (node_screen_x, node_screen_y + node_screen_height / 2.0)),
         ];
 
+        let rotate_handle = (
+            ResizeHandle::Rotate,
+            (node_screen_x + node_screen_width / 2.0, node_screen_y - ROTATE_HANDLE_DISTANCE * self.zoom.max(1.0)),
+        );
+
         // 检查是否在任何一个手柄上
-        for (handle, (hx, hy)) in handles {
+        for (handle, (hx, hy)) in std::iter::once(rotate_handle).chain(handles) {
             if screen_x >= hx - half_handle
                 && screen_x <= hx + half_handle
                 && screen_y >= hy - half_handle
@@ -186,13 +351,8 @@ impl RgRect {
             return ResizeHandle::Bottom;
         }
 
-        // 最后检查是否在矩形内部（用于移动）
-        let is_inside = screen_x >= node_screen_x
-            && screen_x <= node_screen_x + node_screen_width
-            && screen_y >= node_screen_y
-            && screen_y <= node_screen_y + node_screen_height;
-
-        if is_inside {
+        // 最后检查是否在形状内部（用于移动）
+        if self.hit_shape(screen_x, screen_y) {
             ResizeHandle::Body
         } else {
             ResizeHandle::None
@@ -204,6 +364,7 @@ impl RgRect {
             ResizeHandle::TopRight | ResizeHandle::BottomLeft => CursorStyle::ResizeUpRightDownLeft,
             ResizeHandle::Top | ResizeHandle::Bottom => CursorStyle::ResizeUpDown,
             ResizeHandle::Left | ResizeHandle::Right => CursorStyle::ResizeLeftRight,
+            ResizeHandle::Rotate => CursorStyle::Crosshair,
             ResizeHandle::Body => CursorStyle::PointingHand,
             ResizeHandle::None => CursorStyle::Arrow,
         }
@@ -221,6 +382,14 @@ impl RgRect {
                 self.drag_offset = Some((world_x - self.x, world_y - self.y));
             }
 
+            ResizeHandle::Rotate => {
+                self.is_resizing = true;
+                self.resize_handle = handle;
+                let (center_x, center_y) = self.screen_center();
+                let start_angle = (screen_y - center_y).atan2(screen_x - center_x).to_degrees();
+                self.initial_rotation = Some((self.rotation, start_angle));
+            }
+
             handle if handle != ResizeHandle::None => {
                 self.is_resizing = true;
                 self.resize_handle = handle;
@@ -232,89 +401,69 @@ impl RgRect {
 
     // 处理拖拽更新
     pub fn update_drag(&mut self, position: Point<Pixels>) {
+        self.update_drag_with_options(position, RgDragOptions::default());
+    }
+
+    /// 调整大小时是否保持宽高比：图片节点默认保持，按住 Shift 时反过来
+    pub fn keeps_aspect_ratio(&self, options: RgDragOptions) -> bool {
+        options.keep_aspect_ratio != self.image.as_ref().is_some_and(|image| image.lock_aspect_ratio)
+    }
+
+    pub fn update_drag_with_options(&mut self, position: Point<Pixels>, options: RgDragOptions) {
+        let options = RgDragOptions {
+            keep_aspect_ratio: self.keeps_aspect_ratio(options),
+            ..options
+        };
         let screen_x = position.x.to_f64() as f32;
         let screen_y = position.y.to_f64() as f32;
-        let (world_x, world_y) = self.screen_to_world(screen_x, screen_y);
+        let (mut world_x, mut world_y) = self.screen_to_world(screen_x, screen_y);
+
+        if self.is_resizing && self.rotation == 0.0 {
+            if let Some(step) = options.snap_step.filter(|step| *step > 0.0) {
+                world_x = (world_x / step).round() * step;
+                world_y = (world_y / step).round() * step;
+            }
+        }
 
         if self.is_dragging {
             if let Some((offset_x, offset_y)) = self.drag_offset {
                 self.x = world_x - offset_x;
                 self.y = world_y - offset_y;
             }
+        } else if self.resize_handle == ResizeHandle::Rotate {
+            if let Some((initial_rotation, start_angle)) = self.initial_rotation {
+                let (center_x, center_y) = self.screen_center();
+                let angle = (screen_y - center_y).atan2(screen_x - center_x).to_degrees();
+                let mut rotation = initial_rotation + angle - start_angle;
+                if options.snap_rotation {
+                    rotation = (rotation / ROTATION_SNAP_DEGREES).round() * ROTATION_SNAP_DEGREES;
+                }
+                self.rotation = rotation.rem_euclid(360.0);
+            }
         } else if self.is_resizing {
-            if let Some((initial_x, initial_y, initial_width, initial_height)) = self.initial_drag_data {
-                match self.resize_handle {
-                    ResizeHandle::TopLeft => {
-                        let new_width = initial_width + (initial_x - world_x);
-                        let new_height = initial_height + (initial_y - world_y);
-                        if new_width > 10.0 && new_height > 10.0 {
-                            self.x = world_x;
-                            self.y = world_y;
-                            self.width = new_width;
-                            self.height = new_height;
-                        }
-                    }
-
-                    ResizeHandle::Top => {
-                        let new_height = initial_height + (initial_y - world_y);
-                        if new_height > 10.0 {
-                            self.y = world_y;
-                            self.height = new_height;
-                        }
-                    }
-
-                    ResizeHandle::TopRight => {
-                        let new_width = world_x - initial_x;
-                        let new_height = initial_height + (initial_y - world_y);
-                        if new_width > 10.0 && new_height > 10.0 {
-                            self.y = world_y;
-                            self.width = new_width;
-                            self.height = new_height;
-                        }
-                    }
-
-                    ResizeHandle::Right => {
-                        let new_width = world_x - initial_x;
-                        if new_width > 10.0 {
-                            self.width = new_width;
-                        }
-                    }
-
-                    ResizeHandle::BottomRight => {
-                        let new_width = world_x - initial_x;
-                        let new_height = world_y - initial_y;
-                        if new_width > 10.0 && new_height > 10.0 {
-                            self.width = new_width;
-                            self.height = new_height;
-                        }
-                    }
-
-                    ResizeHandle::Bottom => {
-                        let new_height = world_y - initial_y;
-                        if new_height > 10.0 {
-                            self.height = new_height;
-                        }
-                    }
-
-                    ResizeHandle::BottomLeft => {
-                        let new_width = initial_width + (initial_x - world_x);
-                        let new_height = world_y - initial_y;
-                        if new_width > 10.0 && new_height > 10.0 {
-                            self.x = world_x;
-                            self.width = new_width;
-                            self.height = new_height;
-                        }
-                    }
-
-                    ResizeHandle::Left => {
-                        let new_width = initial_width + (initial_x - world_x);
-                        if new_width > 10.0 {
-                            self.x = world_x;
-                            self.width = new_width;
-                        }
-                    }
+            if let Some(initial) = self.initial_drag_data {
+                // 旋转的节点在初始矩形的局部坐标系（绕初始中心反向旋转）中计算新边界
+                let rotation = self.rotation;
+                let initial_center = (initial.0 + initial.2 / 2.0, initial.1 + initial.3 / 2.0);
+                if rotation != 0.0 {
+                    (world_x, world_y) = rotate_point((world_x, world_y), initial_center, -rotation);
+                }
 
-                    _ => {}
+                let (x, y, width, height) = resize_geometry(initial, self.resize_handle, (world_x, world_y), options, (MIN_NODE_SIZE, MIN_NODE_SIZE));
+                self.x = x;
+                self.y = y;
+                self.width = width;
+                self.height = height;
+
+                // 局部坐标系中的新中心映射回世界坐标，对边保持不动
+                if rotation != 0.0 {
+                    let (center_x, center_y) = rotate_point(
+                        (self.x + self.width / 2.0, self.y + self.height / 2.0),
+                        initial_center,
+                        rotation,
+                    );
+                    self.x = center_x - self.width / 2.0;
+                    self.y = center_y - self.height / 2.0;
                 }
             }
         }
@@ -325,6 +474,7 @@ impl RgRect {
         self.is_resizing = false;
         self.drag_offset = None;
         self.initial_drag_data = None;
+        self.initial_rotation = None;
         self.resize_handle = ResizeHandle::None;
     }
 
@@ -338,105 +488,338 @@ impl RgRect {
     }
 }
 
+/// 根据手柄和鼠标位置（未旋转的世界坐标）计算调整大小后的几何数据，宽高不小于 min_size。
+/// 拖过对边时矩形翻转；keep_aspect_ratio 保持宽高比，拖动边中点时另一方向以中心缩放；from_center 以初始中心对称缩放
+pub fn resize_geometry(
+    initial: RgGeometry,
+    handle: ResizeHandle,
+    (world_x, world_y): (f32, f32),
+    options: RgDragOptions,
+    (min_width, min_height): (f32, f32),
+) -> RgGeometry {
+    let (initial_x, initial_y, initial_width, initial_height) = initial;
+    let center = (initial_x + initial_width / 2.0, initial_y + initial_height / 2.0);
+
+    // 手柄在两个方向上移动的边：-1 为起始边，1 为结束边，0 为不移动
+    let (dir_x, dir_y) = match handle {
+        ResizeHandle::TopLeft => (-1.0, -1.0),
+        ResizeHandle::Top => (0.0, -1.0),
+        ResizeHandle::TopRight => (1.0, -1.0),
+        ResizeHandle::Right => (1.0, 0.0),
+        ResizeHandle::BottomRight => (1.0, 1.0),
+        ResizeHandle::Bottom => (0.0, 1.0),
+        ResizeHandle::BottomLeft => (-1.0, 1.0),
+        ResizeHandle::Left => (-1.0, 0.0),
+        _ => return initial,
+    };
+
+    // 不动的一边（或中心）作为锚点，extent 为带符号的新尺寸，负数表示拖过了锚点
+    let axis = |dir: f32, position: f32, start: f32, length: f32, center: f32| -> (f32, f32) {
+        if options.from_center {
+            (center, (position - center) * dir * 2.0)
+        } else {
+            let anchor = if dir > 0.0 { start } else { start + length };
+            (anchor, (position - anchor) * dir)
+        }
+    };
+    let (anchor_x, mut extent_x) = axis(dir_x, world_x, initial_x, initial_width, center.0);
+    let (anchor_y, mut extent_y) = axis(dir_y, world_y, initial_y, initial_height, center.1);
+
+    if options.keep_aspect_ratio && initial_width > 0.0 && initial_height > 0.0 {
+        let scale_x = extent_x / initial_width;
+        let scale_y = extent_y / initial_height;
+        let scale = match (dir_x != 0.0, dir_y != 0.0) {
+            (true, true) => scale_x.abs().max(scale_y.abs()),
+            (true, false) => scale_x.abs(),
+            _ => scale_y.abs(),
+        }
+        .max(min_width / initial_width)
+        .max(min_height / initial_height);
+
+        extent_x = initial_width * scale * if dir_x != 0.0 { scale_x.signum() } else { 1.0 };
+        extent_y = initial_height * scale * if dir_y != 0.0 { scale_y.signum() } else { 1.0 };
+    }
+
+    let place = |dir: f32, anchor: f32, extent: f32, start: f32, length: f32, center: f32, min_size: f32| -> (f32, f32) {
+        let size = extent.abs().max(min_size);
+        if dir == 0.0 {
+            if options.keep_aspect_ratio {
+                (center - size / 2.0, size)
+            } else {
+                (start, length)
+            }
+        } else if options.from_center {
+            (center - size / 2.0, size)
+        } else {
+            let far = anchor + dir * size * if extent < 0.0 { -1.0 } else { 1.0 };
+            (anchor.min(far), size)
+        }
+    };
+    let (x, width) = place(dir_x, anchor_x, extent_x, initial_x, initial_width, center.0, min_width);
+    let (y, height) = place(dir_y, anchor_y, extent_y, initial_y, initial_height, center.1, min_height);
+
+    (x, y, width, height)
+}
+
+// 将点绕 center 顺时针旋转 degrees 度（屏幕坐标系 y 轴向下）
+fn rotate_point((x, y): (f32, f32), (center_x, center_y): (f32, f32), degrees: f32) -> (f32, f32) {
+    let (sin, cos) = degrees.to_radians().sin_cos();
+    let (dx, dy) = (x - center_x, y - center_y);
+    (center_x + dx * cos - dy * sin, center_y + dx * sin + dy * cos)
+}
+
+// 四条边设置相同的边框宽度（屏幕像素）
+fn border_width(element: Div, width: f32) -> Div {
+    element
+        .border_t(px(width))
+        .border_r(px(width))
+        .border_b(px(width))
+        .border_l(px(width))
+}
+
+// 将折线拆成虚线段，closed 时包括最后一点到第一点的线段
+fn dash_segments(points: &[(f32, f32)], closed: bool, dash: f32, gap: f32) -> Vec<((f32, f32), (f32, f32))> {
+    let mut segments = Vec::new();
+    if points.len() < 2 || dash <= 0.0 {
+        return segments;
+    }
+
+    // 当前位置在一个 dash + gap 周期中的偏移
+    let period = dash + gap.max(0.0);
+    let mut phase = 0.0;
+    let segment_count = if closed { points.len() } else { points.len() - 1 };
+    for index in 0..segment_count {
+        let from = points[index];
+        let to = points[(index + 1) % points.len()];
+        let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+        let length = (dx * dx + dy * dy).sqrt();
+        if length == 0.0 {
+            continue;
+        }
+
+        let mut travelled = 0.0;
+        while travelled < length {
+            let step = if phase < dash { dash - phase } else { period - phase };
+            let next = (travelled + step).min(length);
+            if phase < dash {
+                let a = (from.0 + dx * travelled / length, from.1 + dy * travelled / length);
+                let b = (from.0 + dx * next / length, from.1 + dy * next / length);
+                segments.push((a, b));
+            }
+            phase = (phase + next - travelled) % period;
+            travelled = next;
+        }
+    }
+    segments
+}
+
 //************************************************************************** Render **********************************************************************************//
 impl Render for RgRect {
     fn render(&mut self, _window: &mut Window, _cx: &mut Context<Self>) -> impl IntoElement {
         let (screen_x, screen_y) = self.screen_position();
         let (screen_width, screen_height) = self.screen_size();
-
-        let border_color = if self.selected {
-            rgb(0x00ff00)
-        } else {
-            rgb(0x505050)
-        };
+        // 旋转或非矩形的形状由路径绘制，否则直接用 div 的背景和边框
+        let path_rendered = self.rotation != 0.0 || !self.shape.is_box();
+        let closed = self.shape.is_closed();
+        let style = self.style;
+        let zoom = self.zoom;
 
         let handle_size = 12.0 * self.zoom.max(1.0);
         let half_handle = handle_size / 2.0;
 
-        let base_font_size = 14.0;
-        let scaled_font_size = base_font_size * self.zoom;
+        let font_size = style.text_size * zoom;
+        let stroke_width = style.stroke_width.max(0.0) * zoom;
+        let corner_radius = self.corner_radius() * zoom;
 
-        let base_rect = div()
+        // 文字始终保持水平，路径绘制的形状本体在文字下方
+        let mut base_rect = div()
             .absolute()
             .left(px(screen_x))
             .top(px(screen_y))
             .w(px(screen_width))
             .h(px(screen_height))
-            .bg(rgba(0xf0f0f0aa))
-            .border_1()
-            .border_color(border_color)
-            .text_color(black())
-            .text_size(px(scaled_font_size))
-            .line_height(px(scaled_font_size * 1.2))
+            .text_color(style.text())
+            .text_size(px(font_size))
+            .line_height(px(font_size * 1.2))
+            .px(px(LABEL_PADDING * zoom))
+            .overflow_hidden()
             .flex()
-            .items_center()
-            .justify_center()
-            .child(format!("Rect {}", self.id));
+            .items_center();
+        base_rect = match style.text_align {
+            RgTextAlign::Left => base_rect.justify_start().text_left(),
+            RgTextAlign::Center => base_rect.justify_center().text_center(),
+            RgTextAlign::Right => base_rect.justify_end().text_right(),
+        };
+        // 图片铺满节点，按缩放方式放置，标签叠加在图片之上
+        if let Some(image) = &self.image {
+            base_rect = base_rect.child(
+                img(image.image_source())
+                    .object_fit(image.fit.object_fit())
+                    .absolute()
+                    .left(px(0.0))
+                    .top(px(0.0))
+                    .w(px(screen_width))
+                    .h(px(screen_height)),
+            );
+        }
 
-        if !self.selected {
-            return base_rect;
+        // 文字在节点宽度内折行
+        if !self.is_editing_label {
+            base_rect = base_rect.child(div().w_full().child(self.label.clone()));
         }
 
-        let mut container = div().absolute().size_full();
+        if !path_rendered {
+            base_rect = base_rect
+                .bg(style.fill())
+                .rounded(px(corner_radius));
 
-        container = container.child(base_rect);
+            if stroke_width > 0.0 {
+                base_rect = border_width(base_rect, stroke_width).border_color(style.stroke());
+                if style.stroke_dash == RgStrokeDash::Dashed {
+                    base_rect = base_rect.border_dashed();
+                }
+            }
+
+            if let Some(shadow) = style.shadow {
+                base_rect = base_rect.shadow(vec![BoxShadow {
+                    color: shadow.color().into(),
+                    offset: point(px(shadow.offset_x * zoom), px(shadow.offset_y * zoom)),
+                    blur_radius: px(shadow.blur * zoom),
+                    spread_radius: px(0.0),
+                }]);
+            }
+
+            if !self.selected {
+                return base_rect;
+            }
+        }
+
+        let mut container = div().absolute().size_full();
 
         let edge_highlight_color = rgba(0x008aff88);
         let edge_size = 2.0 * self.zoom.max(0.5);
 
-        container = container.child(
-            div()
-                .absolute()
-                .left(px(screen_x))
-                .top(px(screen_y))
-                .w(px(screen_width))
-                .h(px(edge_size))
-                .bg(edge_highlight_color),
-        );
+        if path_rendered {
+            let outline: Vec<(f32, f32)> = self.world_outline()
+                .into_iter()
+                .map(|(x, y)| self.world_to_screen(x, y))
+                .collect();
+            let selected = self.selected;
+
+            container = container.child(
+                canvas(
+                    |_, _, _| {},
+                    move |bounds, _, window, _| {
+                        let to_point = |(x, y): (f32, f32)| bounds.origin + point(px(x), px(y));
+                        let points: Vec<Point<Pixels>> = outline.iter().copied().map(to_point).collect();
+
+                        // 路径绘制的投影不做模糊，只按偏移绘制；不闭合的形状没有填充和投影
+                        if closed {
+                            if let Some(shadow) = style.shadow {
+                                let offset = point(px(shadow.offset_x * zoom), px(shadow.offset_y * zoom));
+                                let shadow_points: Vec<Point<Pixels>> = points.iter().map(|p| *p + offset).collect();
+                                let mut path = PathBuilder::fill();
+                                path.add_polygon(&shadow_points, true);
+                                if let Ok(path) = path.build() {
+                                    window.paint_path(path, shadow.color());
+                                }
+                            }
+
+                            let mut fill = PathBuilder::fill();
+                            fill.add_polygon(&points, true);
+                            if let Ok(path) = fill.build() {
+                                window.paint_path(path, style.fill());
+                            }
+                        }
 
-        container = container.child(
-            div()
-                .absolute()
-                .left(px(screen_x))
-                .top(px(screen_y + screen_height - edge_size))
-                .w(px(screen_width))
-                .h(px(edge_size))
-                .bg(edge_highlight_color),
-        );
+                        if stroke_width > 0.0 {
+                            let mut border = PathBuilder::stroke(px(stroke_width));
+                            match style.stroke_dash {
+                                RgStrokeDash::Solid => border.add_polygon(&points, closed),
+                                RgStrokeDash::Dashed => {
+                                    for (from, to) in dash_segments(&outline, closed, DASH_LENGTH * zoom, DASH_GAP * zoom) {
+                                        border.move_to(to_point(from));
+                                        border.line_to(to_point(to));
+                                    }
+                                }
+                            }
+                            if let Ok(path) = border.build() {
+                                window.paint_path(path, style.stroke());
+                            }
+                        }
 
-        container = container.child(
-            div()
+                        // 选中高亮叠加在边框之上
+                        if selected {
+                            let mut highlight = PathBuilder::stroke(px(edge_size));
+                            highlight.add_polygon(&points, closed);
+                            if let Ok(path) = highlight.build() {
+                                window.paint_path(path, edge_highlight_color);
+                            }
+                        }
+                    },
+                )
                 .absolute()
-                .left(px(screen_x))
-                .top(px(screen_y))
-                .w(px(edge_size))
-                .h(px(screen_height))
-                .bg(edge_highlight_color),
-        );
+                .size_full(),
+            );
+        }
 
-        container = container.child(
-            div()
-                .absolute()
-                .left(px(screen_x + screen_width - edge_size))
-                .top(px(screen_y))
-                .w(px(edge_size))
-                .h(px(screen_height))
-                .bg(edge_highlight_color),
-        );
+        container = container.child(base_rect);
+
+        if !self.selected {
+            return container;
+        }
+
+        // 选中高亮叠加在边框之上，不改变节点自身的边框
+        if !path_rendered {
+            container = container.child(
+                border_width(
+                    div()
+                        .absolute()
+                        .left(px(screen_x))
+                        .top(px(screen_y))
+                        .w(px(screen_width))
+                        .h(px(screen_height)),
+                    edge_size,
+                )
+                .border_color(edge_highlight_color)
+                .rounded(px(corner_radius)),
+            );
+        }
 
         if self.show_handles {
+            // 旋转手柄与上边缘中点之间的连线
+            let (top_x, top_y) = self.get_handle_position(ResizeHandle::Top);
+            let (rotate_x, rotate_y) = self.get_handle_position(ResizeHandle::Rotate);
+            container = container.child(
+                canvas(
+                    |_, _, _| {},
+                    move |bounds, _, window, _| {
+                        let mut line = PathBuilder::stroke(px(1.0));
+                        line.move_to(bounds.origin + point(px(top_x), px(top_y)));
+                        line.line_to(bounds.origin + point(px(rotate_x), px(rotate_y)));
+                        if let Ok(path) = line.build() {
+                            window.paint_path(path, rgb(0x0000ff));
+                        }
+                    },
+                )
+                .absolute()
+                .size_full(),
+            );
+
             let handles = [
-                (ResizeHandle::TopLeft, (screen_x, screen_y)),
-                (ResizeHandle::Top, (screen_x + screen_width / 2.0, screen_y)),
-                (ResizeHandle::TopRight, (screen_x + screen_width, screen_y)),
-                (ResizeHandle::Right, (screen_x + screen_width, screen_y + screen_height / 2.0)),
-                (ResizeHandle::BottomRight, (screen_x + screen_width, screen_y + screen_height)),
-                (ResizeHandle::Bottom, (screen_x + screen_width / 2.0, screen_y + screen_height)),
-                (ResizeHandle::BottomLeft, (screen_x, screen_y + screen_height)),
-                (ResizeHandle::Left, (screen_x, screen_y + screen_height / 2.0)),
+                ResizeHandle::TopLeft,
+                ResizeHandle::Top,
+                ResizeHandle::TopRight,
+                ResizeHandle::Right,
+                ResizeHandle::BottomRight,
+                ResizeHandle::Bottom,
+                ResizeHandle::BottomLeft,
+                ResizeHandle::Left,
+                ResizeHandle::Rotate,
             ];
 
-            for (handle, (hx, hy)) in handles {
+            for handle in handles {
+                let (hx, hy) = self.get_handle_position(handle);
                 let handle_color = match handle {
                     ResizeHandle::TopLeft |
                     ResizeHandle::TopRight |
@@ -448,8 +831,15 @@ impl Render for RgRect {
                     ResizeHandle::Left |
                     ResizeHandle::Right => rgb(0x0000ff),
 
+                    ResizeHandle::Rotate => rgb(0x00c060),
+
                     _ => rgb(0xffffff),
                 };
+                let radius = if handle == ResizeHandle::Rotate {
+                    half_handle
+                } else {
+                    handle_size / 4.0
+                };
 
                 container = container.child(
                     div()
@@ -461,7 +851,7 @@ impl Render for RgRect {
                         .bg(handle_color)
                         .border_1()
                         .border_color(rgb(0x000000))
-                        .rounded(px(handle_size / 4.0))
+                        .rounded(px(radius))
                 );
             }
         }
@@ -469,3 +859,89 @@ impl Render for RgRect {
         container
     }
 }
+
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    use super::*;
+
+    const INITIAL: RgGeometry = (0.0, 0.0, 100.0, 50.0);
+    const MIN_SIZE: (f32, f32) = (10.0, 10.0);
+    const HANDLES: [ResizeHandle; 8] = [
+        ResizeHandle::TopLeft,
+        ResizeHandle::Top,
+        ResizeHandle::TopRight,
+        ResizeHandle::Right,
+        ResizeHandle::BottomRight,
+        ResizeHandle::Bottom,
+        ResizeHandle::BottomLeft,
+        ResizeHandle::Left,
+    ];
+
+    fn options(keep_aspect_ratio: bool, from_center: bool) -> RgDragOptions {
+        RgDragOptions { keep_aspect_ratio, from_center, ..Default::default() }
+    }
+
+    fn resize(handle: ResizeHandle, point: (f32, f32), options: RgDragOptions) -> RgGeometry {
+        resize_geometry(INITIAL, handle, point, options, MIN_SIZE)
+    }
+
+    #[test]
+    fn shift_keeps_aspect_ratio() {
+        let shift = options(true, false);
+
+        // 角手柄取两个方向中较大的缩放比例，对角保持不动
+        assert_eq!(resize(ResizeHandle::BottomRight, (300.0, 60.0), shift), (0.0, 0.0, 300.0, 150.0));
+        assert_eq!(resize(ResizeHandle::TopLeft, (-50.0, 40.0), shift), (-50.0, -25.0, 150.0, 75.0));
+        // 边手柄按该方向缩放，另一方向以中心对称调整
+        assert_eq!(resize(ResizeHandle::Right, (200.0, 0.0), shift), (0.0, -25.0, 200.0, 100.0));
+        assert_eq!(resize(ResizeHandle::Top, (0.0, -50.0), shift), (-50.0, -50.0, 200.0, 100.0));
+    }
+
+    #[test]
+    fn alt_keeps_centre_fixed() {
+        let alt = options(false, true);
+        let center = |(x, y, width, height): RgGeometry| (x + width / 2.0, y + height / 2.0);
+
+        assert_eq!(resize(ResizeHandle::Right, (150.0, 0.0), alt), (-50.0, 0.0, 200.0, 50.0));
+        assert_eq!(resize(ResizeHandle::BottomRight, (80.0, 45.0), alt), (20.0, 5.0, 60.0, 40.0));
+        assert_eq!(resize(ResizeHandle::TopLeft, (-10.0, -5.0), alt), (-10.0, -5.0, 120.0, 60.0));
+
+        // Shift + Alt 同时保持中心和宽高比
+        let both = resize(ResizeHandle::BottomRight, (150.0, 30.0), options(true, true));
+        assert_eq!(both, (-50.0, -25.0, 200.0, 100.0));
+        assert_eq!(center(both), center(INITIAL));
+    }
+
+    #[test]
+    fn dragging_past_anchor_mirrors_rect() {
+        let none = RgDragOptions::default();
+
+        assert_eq!(resize(ResizeHandle::BottomRight, (-60.0, -30.0), none), (-60.0, -30.0, 60.0, 30.0));
+        assert_eq!(resize(ResizeHandle::Left, (160.0, 0.0), none), (100.0, 0.0, 60.0, 50.0));
+        assert_eq!(resize(ResizeHandle::Top, (0.0, 80.0), none), (0.0, 50.0, 100.0, 30.0));
+        // 保持宽高比时翻转后宽高比不变
+        assert_eq!(resize(ResizeHandle::BottomRight, (-200.0, -10.0), options(true, false)), (-200.0, -100.0, 200.0, 100.0));
+    }
+
+    #[test]
+    fn never_shrinks_below_min_size() {
+        assert_eq!(resize(ResizeHandle::BottomRight, (3.0, 2.0), RgDragOptions::default()), (0.0, 0.0, 10.0, 10.0));
+        assert_eq!(resize(ResizeHandle::Left, (99.5, 0.0), RgDragOptions::default()), (90.0, 0.0, 10.0, 50.0));
+        // 保持宽高比时按较大的最小比例缩放
+        assert_eq!(resize(ResizeHandle::BottomRight, (1.0, 1.0), options(true, false)), (0.0, 0.0, 20.0, 10.0));
+        assert_eq!(resize(ResizeHandle::Right, (52.0, 0.0), options(false, true)), (45.0, 0.0, 10.0, 50.0));
+
+        let mut rng = StdRng::seed_from_u64(12);
+        for _ in 0..2000 {
+            let handle = HANDLES[rng.gen_range(0..HANDLES.len())];
+            let point = (rng.gen_range(-300.0..300.0), rng.gen_range(-300.0..300.0));
+            let options = options(rng.gen_bool(0.5), rng.gen_bool(0.5));
+            let (_, _, width, height) = resize(handle, point, options);
+            assert!(width >= MIN_SIZE.0 && height >= MIN_SIZE.1, "{handle:?} {point:?} {options:?}: {width}x{height}");
+        }
+    }
+}
@@ -0,0 +1,356 @@
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+
+use crate::*;
+
+//************************************************************************** RgOrthogonalRouter **********************************************************************************//
+// 网格节点数上限，超过时放弃搜索，由调用方退回简单折线
+const MAX_GRID_POINTS: usize = 40_000;
+const EPSILON: f32 = 0.01;
+// 方向：右、下、左、上，NO_DIRECTION 表示起点没有指定方向
+const DIRECTIONS: [(i32, i32); 4] = [(1, 0), (0, 1), (-1, 0), (0, -1)];
+const NO_DIRECTION: usize = 4;
+
+/// 正交连线路由：在由障碍物边界构成的稀疏网格上做 A* 搜索，代价为长度、转折和与其他连线的交叉
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RgOrthogonalRouter {
+    /// 路径与障碍物之间保持的距离（世界坐标）
+    pub margin: f32,
+    /// 每次转折的额外代价，越大转折越少
+    pub bend_penalty: f32,
+    /// 与其他连线交叉一次的额外代价
+    pub crossing_penalty: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct RgRouteState {
+    cost: f32,
+    estimate: f32,
+    state: usize,
+}
+
+//************************************************************************** Trait **********************************************************************************//
+impl RgOrthogonalRouter {
+    /// 计算从 start 沿 start_direction 离开、沿 end_direction 反方向到达 end 的正交路径。
+    /// obstacles 为需要绕开的节点包围盒（包括两端节点），others 为已有连线，用于减少交叉。
+    /// 找不到路径时返回 None
+    pub fn route(
+        &self,
+        (start, start_direction): ((f32, f32), (f32, f32)),
+        (end, end_direction): ((f32, f32), (f32, f32)),
+        obstacles: &[RgGeometry],
+        others: &[Vec<(f32, f32)>],
+    ) -> Option<Vec<(f32, f32)>> {
+        let margin = self.margin.max(0.0);
+        let start_direction = axis_direction(start_direction, (end.0 - start.0, end.1 - start.1));
+        let end_direction = axis_direction(end_direction, (start.0 - end.0, start.1 - end.1));
+
+        let stub = |point: (f32, f32), direction: Option<usize>| match direction {
+            Some(index) => {
+                let (dx, dy) = DIRECTIONS[index];
+                (point.0 + dx as f32 * margin, point.1 + dy as f32 * margin)
+            }
+            None => point,
+        };
+        let start_stub = stub(start, start_direction);
+        let end_stub = stub(end, end_direction);
+
+        let obstacles: Vec<RgGeometry> = obstacles.iter()
+            .map(|(x, y, width, height)| (x - margin, y - margin, width + margin * 2.0, height + margin * 2.0))
+            .collect();
+
+        //****************************** 1.稀疏网格 ****************************//
+        let mut xs = vec![start_stub.0, end_stub.0, (start_stub.0 + end_stub.0) / 2.0];
+        let mut ys = vec![start_stub.1, end_stub.1, (start_stub.1 + end_stub.1) / 2.0];
+        for (x, y, width, height) in &obstacles {
+            xs.extend([*x, x + width]);
+            ys.extend([*y, y + height]);
+        }
+        let xs = sorted_coordinates(xs);
+        let ys = sorted_coordinates(ys);
+        if xs.len() * ys.len() > MAX_GRID_POINTS {
+            return None;
+        }
+
+        let index_of = |values: &[f32], value: f32| values.iter().position(|v| (v - value).abs() < EPSILON);
+        let start_index = (index_of(&xs, start_stub.0)?, index_of(&ys, start_stub.1)?);
+        let end_index = (index_of(&xs, end_stub.0)?, index_of(&ys, end_stub.1)?);
+
+        //****************************** 2.A* 搜索 ****************************//
+        let width = xs.len();
+        let height = ys.len();
+        let encode = |x: usize, y: usize, direction: usize| (y * width + x) * 5 + direction;
+        let decode = |state: usize| ((state / 5) % width, state / 5 / width, state % 5);
+        let heuristic = |x: usize, y: usize| (xs[x] - end_stub.0).abs() + (ys[y] - end_stub.1).abs();
+        // 到达终点时需要的方向（与终点锚点方向相反）
+        let arrive_direction = end_direction.map(|index| (index + 2) % 4);
+
+        let start_state = encode(start_index.0, start_index.1, start_direction.unwrap_or(NO_DIRECTION));
+        let goal_state = width * height * 5;
+
+        let mut best: HashMap<usize, f32> = HashMap::from([(start_state, 0.0)]);
+        let mut came_from: HashMap<usize, usize> = HashMap::new();
+        let mut open = BinaryHeap::from([RgRouteState {
+            cost: 0.0,
+            estimate: heuristic(start_index.0, start_index.1),
+            state: start_state,
+        }]);
+
+        let mut found = false;
+        while let Some(RgRouteState { cost, state, .. }) = open.pop() {
+            if state == goal_state {
+                found = true;
+                break;
+            }
+            if best.get(&state).is_some_and(|b| cost > *b) {
+                continue;
+            }
+
+            let (x, y, direction) = decode(state);
+
+            // 到达终点前的延伸点后，再加上最后一次转折的代价作为终点
+            if (x, y) == end_index {
+                let bend = match arrive_direction {
+                    Some(arrive) if direction != NO_DIRECTION && direction != arrive => self.bend_penalty,
+                    _ => 0.0,
+                };
+                let goal_cost = cost + bend;
+                if best.get(&goal_state).is_none_or(|b| goal_cost < *b) {
+                    best.insert(goal_state, goal_cost);
+                    came_from.insert(goal_state, state);
+                    open.push(RgRouteState { cost: goal_cost, estimate: goal_cost, state: goal_state });
+                }
+            }
+
+            for (next_direction, (dx, dy)) in DIRECTIONS.iter().enumerate() {
+                // 不允许原路折返
+                if direction != NO_DIRECTION && next_direction == (direction + 2) % 4 {
+                    continue;
+                }
+                let next_x = x as i32 + dx;
+                let next_y = y as i32 + dy;
+                if next_x < 0 || next_y < 0 || next_x >= width as i32 || next_y >= height as i32 {
+                    continue;
+                }
+                let (next_x, next_y) = (next_x as usize, next_y as usize);
+
+                let from = (xs[x], ys[y]);
+                let to = (xs[next_x], ys[next_y]);
+                let middle = ((from.0 + to.0) / 2.0, (from.1 + to.1) / 2.0);
+                if obstacles.iter().any(|obstacle| contains_strictly(*obstacle, middle)) {
+                    continue;
+                }
+
+                let mut step = (to.0 - from.0).abs() + (to.1 - from.1).abs();
+                if direction != NO_DIRECTION && direction != next_direction {
+                    step += self.bend_penalty;
+                }
+                step += crossings(from, to, others) as f32 * self.crossing_penalty;
+
+                let next_state = encode(next_x, next_y, next_direction);
+                let next_cost = cost + step;
+                if best.get(&next_state).is_none_or(|b| next_cost < *b) {
+                    best.insert(next_state, next_cost);
+                    came_from.insert(next_state, state);
+                    open.push(RgRouteState {
+                        cost: next_cost,
+                        estimate: next_cost + heuristic(next_x, next_y),
+                        state: next_state,
+                    });
+                }
+            }
+        }
+
+        if !found {
+            return None;
+        }
+
+        //****************************** 3.还原路径 ****************************//
+        let mut grid_points = Vec::new();
+        let mut state = came_from.get(&goal_state).copied();
+        while let Some(current) = state {
+            let (x, y, _) = decode(current);
+            grid_points.push((xs[x], ys[y]));
+            state = came_from.get(&current).copied();
+        }
+        grid_points.reverse();
+
+        let mut points = vec![start];
+        points.extend(grid_points);
+        points.push(end);
+        Some(simplify(points))
+    }
+}
+
+impl Default for RgOrthogonalRouter {
+    fn default() -> Self {
+        Self {
+            margin: 16.0,
+            bend_penalty: 40.0,
+            crossing_penalty: 60.0,
+        }
+    }
+}
+
+impl Eq for RgRouteState {}
+
+impl Ord for RgRouteState {
+    // BinaryHeap 为大顶堆，估计值小的优先
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.estimate.total_cmp(&self.estimate).then_with(|| self.state.cmp(&other.state))
+    }
+}
+
+impl PartialOrd for RgRouteState {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// 将方向吸附到最接近的坐标轴，没有方向时朝向另一端
+fn axis_direction(direction: (f32, f32), fallback: (f32, f32)) -> Option<usize> {
+    let (dx, dy) = if direction.0.abs() > EPSILON || direction.1.abs() > EPSILON {
+        direction
+    } else {
+        fallback
+    };
+    if dx.abs() <= EPSILON && dy.abs() <= EPSILON {
+        return None;
+    }
+
+    Some(if dx.abs() >= dy.abs() {
+        if dx > 0.0 { 0 } else { 2 }
+    } else if dy > 0.0 {
+        1
+    } else {
+        3
+    })
+}
+
+fn sorted_coordinates(mut values: Vec<f32>) -> Vec<f32> {
+    values.sort_by(|a, b| a.total_cmp(b));
+    values.dedup_by(|a, b| (*a - *b).abs() < EPSILON);
+    values
+}
+
+fn contains_strictly((x, y, width, height): RgGeometry, point: (f32, f32)) -> bool {
+    point.0 > x + EPSILON && point.0 < x + width - EPSILON && point.1 > y + EPSILON && point.1 < y + height - EPSILON
+}
+
+// 线段与其他连线中垂直线段的交叉次数
+fn crossings(from: (f32, f32), to: (f32, f32), others: &[Vec<(f32, f32)>]) -> usize {
+    let horizontal = (from.1 - to.1).abs() < EPSILON;
+    let between = |value: f32, a: f32, b: f32| value > a.min(b) + EPSILON && value < a.max(b) - EPSILON;
+
+    others.iter()
+        .flat_map(|points| points.windows(2))
+        .filter(|segment| {
+            let (a, b) = (segment[0], segment[1]);
+            if horizontal {
+                (a.0 - b.0).abs() < EPSILON && between(a.0, from.0, to.0) && between(from.1, a.1, b.1)
+            } else {
+                (a.1 - b.1).abs() < EPSILON && between(a.1, from.1, to.1) && between(from.0, a.0, b.0)
+            }
+        })
+        .count()
+}
+
+// 去掉重复点和共线的中间点
+fn simplify(points: Vec<(f32, f32)>) -> Vec<(f32, f32)> {
+    let mut result: Vec<(f32, f32)> = Vec::with_capacity(points.len());
+
+    for point in points {
+        if result.last().is_some_and(|last| (last.0 - point.0).abs() < EPSILON && (last.1 - point.1).abs() < EPSILON) {
+            continue;
+        }
+        if result.len() >= 2 {
+            let a = result[result.len() - 2];
+            let b = result[result.len() - 1];
+            let collinear = ((a.0 - b.0).abs() < EPSILON && (b.0 - point.0).abs() < EPSILON)
+                || ((a.1 - b.1).abs() < EPSILON && (b.1 - point.1).abs() < EPSILON);
+            if collinear {
+                result.pop();
+            }
+        }
+        result.push(point);
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RIGHT: (f32, f32) = (1.0, 0.0);
+    const LEFT: (f32, f32) = (-1.0, 0.0);
+
+    fn is_orthogonal(points: &[(f32, f32)]) -> bool {
+        points.windows(2).all(|segment| {
+            (segment[0].0 - segment[1].0).abs() < EPSILON || (segment[0].1 - segment[1].1).abs() < EPSILON
+        })
+    }
+
+    // 正交线段是否穿过矩形内部
+    fn crosses_interior((x, y, width, height): RgGeometry, a: (f32, f32), b: (f32, f32)) -> bool {
+        a.0.max(b.0) > x && a.0.min(b.0) < x + width && a.1.max(b.1) > y && a.1.min(b.1) < y + height
+    }
+
+    #[test]
+    fn straight_route_without_obstacles() {
+        let route = RgOrthogonalRouter::default().route(((0.0, 0.0), RIGHT), ((400.0, 0.0), LEFT), &[], &[]);
+        assert_eq!(route, Some(vec![(0.0, 0.0), (400.0, 0.0)]));
+    }
+
+    #[test]
+    fn route_avoids_obstacles() {
+        let router = RgOrthogonalRouter::default();
+        let obstacles = [(150.0, -100.0, 100.0, 200.0), (-20.0, 150.0, 440.0, 40.0)];
+        let route = router.route(((0.0, 0.0), RIGHT), ((400.0, 0.0), LEFT), &obstacles, &[]).unwrap();
+
+        assert_eq!(route.first(), Some(&(0.0, 0.0)));
+        assert_eq!(route.last(), Some(&(400.0, 0.0)));
+        assert!(is_orthogonal(&route));
+        for segment in route.windows(2) {
+            for obstacle in &obstacles {
+                assert!(!crosses_interior(*obstacle, segment[0], segment[1]), "{segment:?} crosses {obstacle:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn route_to_same_point() {
+        let route = RgOrthogonalRouter::default().route(((50.0, 50.0), (0.0, 0.0)), ((50.0, 50.0), (0.0, 0.0)), &[], &[]);
+        assert_eq!(route, Some(vec![(50.0, 50.0)]));
+    }
+
+    #[test]
+    fn no_route_out_of_enclosed_start() {
+        // 起点被障碍物完全包住，任何方向的第一段都会穿过障碍物
+        let router = RgOrthogonalRouter { margin: 0.0, ..Default::default() };
+        let obstacles = [(-100.0, -100.0, 200.0, 200.0)];
+        let route = router.route(((0.0, 0.0), RIGHT), ((400.0, 0.0), LEFT), &obstacles, &[]);
+        assert_eq!(route, None);
+    }
+
+    #[test]
+    fn simplify_removes_duplicate_and_collinear_points() {
+        let points = vec![(0.0, 0.0), (5.0, 0.0), (5.0, 0.0), (10.0, 0.0), (10.0, 5.0), (10.0, 10.0), (20.0, 10.0)];
+        assert_eq!(simplify(points), vec![(0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (20.0, 10.0)]);
+    }
+
+    #[test]
+    fn axis_direction_snaps_to_nearest_axis() {
+        assert_eq!(axis_direction((0.2, -3.0), (1.0, 0.0)), Some(3));
+        assert_eq!(axis_direction((0.0, 0.0), (-1.0, 0.5)), Some(2));
+        assert_eq!(axis_direction((0.0, 0.0), (0.0, 0.0)), None);
+    }
+
+    #[test]
+    fn crossings_count_only_interior_intersections() {
+        let others = vec![vec![(5.0, -5.0), (5.0, 5.0)], vec![(10.0, -5.0), (10.0, 5.0)]];
+        assert_eq!(crossings((0.0, 0.0), (10.0, 0.0), &others), 1);
+        assert_eq!(crossings((0.0, 0.0), (20.0, 0.0), &others), 2);
+        assert_eq!(crossings((0.0, 10.0), (20.0, 10.0), &others), 0);
+    }
+}
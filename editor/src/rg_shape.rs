@@ -0,0 +1,168 @@
+use serde::{Deserialize, Serialize};
+
+use crate::*;
+
+//************************************************************************** RgShape **********************************************************************************//
+// 椭圆展开的线段数
+const ELLIPSE_SEGMENTS: usize = 48;
+// 每个圆角展开的线段数
+const CORNER_SEGMENTS: usize = 6;
+// 圆角矩形在样式没有设置圆角时的圆角半径（短边的比例）
+const DEFAULT_ROUNDED_RATIO: f32 = 0.2;
+
+/// 节点的形状。多边形、直线和手绘路径的点相对节点包围盒归一化到 0.0 ~ 1.0，调整大小时随包围盒缩放
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub enum RgShape {
+    /// 圆角半径取样式的 corner_radius
+    #[default]
+    Rectangle,
+    /// 样式没有设置圆角时使用默认圆角
+    RoundedRectangle,
+    Ellipse,
+    Diamond,
+    /// 顶点在上边中点的等腰三角形
+    Triangle,
+    /// 闭合多边形
+    Polygon { points: Vec<(f32, f32)> },
+    /// 直线段，不填充
+    Line { start: (f32, f32), end: (f32, f32) },
+    /// 手绘路径，不闭合也不填充
+    Path { points: Vec<(f32, f32)> },
+}
+
+//************************************************************************** Trait **********************************************************************************//
+impl RgShape {
+    /// 由世界坐标的点创建多边形（closed）或手绘路径，返回节点的包围盒和形状。
+    /// 水平或竖直方向没有跨度时包围盒在该方向扩展到 MIN_NODE_SIZE
+    pub fn from_world_points(points: &[(f32, f32)], closed: bool) -> Option<(RgGeometry, RgShape)> {
+        if points.len() < 2 {
+            return None;
+        }
+
+        let (min_x, min_y, max_x, max_y) = points.iter().fold(
+            (f32::INFINITY, f32::INFINITY, f32::NEG_INFINITY, f32::NEG_INFINITY),
+            |(a, b, c, d), (x, y)| (a.min(*x), b.min(*y), c.max(*x), d.max(*y)),
+        );
+        let axis = |min: f32, max: f32| {
+            let size = (max - min).max(MIN_NODE_SIZE);
+            ((min + max) / 2.0 - size / 2.0, size)
+        };
+        let (x, width) = axis(min_x, max_x);
+        let (y, height) = axis(min_y, max_y);
+
+        let points: Vec<(f32, f32)> = points.iter().map(|(px, py)| ((px - x) / width, (py - y) / height)).collect();
+        let shape = if closed {
+            RgShape::Polygon { points }
+        } else if points.len() == 2 {
+            RgShape::Line { start: points[0], end: points[1] }
+        } else {
+            RgShape::Path { points }
+        };
+        Some(((x, y, width, height), shape))
+    }
+
+    /// 闭合形状可以填充，不闭合的形状只绘制线条
+    pub fn is_closed(&self) -> bool {
+        !matches!(self, RgShape::Line { .. } | RgShape::Path { .. })
+    }
+
+    /// 能否直接用带圆角和边框的 div 绘制
+    pub fn is_box(&self) -> bool {
+        matches!(self, RgShape::Rectangle | RgShape::RoundedRectangle)
+    }
+
+    /// 实际使用的圆角半径（世界坐标），只有矩形和圆角矩形有圆角
+    pub fn corner_radius(&self, style: &RgStyle, width: f32, height: f32) -> f32 {
+        match self {
+            RgShape::Rectangle => style.clamped_corner_radius(width, height),
+            RgShape::RoundedRectangle if style.corner_radius > 0.0 => style.clamped_corner_radius(width, height),
+            RgShape::RoundedRectangle => width.abs().min(height.abs()) * DEFAULT_ROUNDED_RATIO,
+            _ => 0.0,
+        }
+    }
+
+    /// 未旋转的轮廓（相对包围盒左上角，世界坐标尺寸），顺时针排列。不闭合的形状为折线
+    pub fn outline(&self, width: f32, height: f32, corner_radius: f32) -> Vec<(f32, f32)> {
+        let scale = |(x, y): &(f32, f32)| (x * width, y * height);
+
+        match self {
+            RgShape::Rectangle | RgShape::RoundedRectangle => rounded_rect_outline(width, height, corner_radius),
+            RgShape::Ellipse => (0..ELLIPSE_SEGMENTS)
+                .map(|i| {
+                    let angle = std::f32::consts::TAU * i as f32 / ELLIPSE_SEGMENTS as f32;
+                    (width / 2.0 * (1.0 + angle.cos()), height / 2.0 * (1.0 + angle.sin()))
+                })
+                .collect(),
+            RgShape::Diamond => vec![(width / 2.0, 0.0), (width, height / 2.0), (width / 2.0, height), (0.0, height / 2.0)],
+            RgShape::Triangle => vec![(width / 2.0, 0.0), (width, height), (0.0, height)],
+            RgShape::Polygon { points } | RgShape::Path { points } => points.iter().map(scale).collect(),
+            RgShape::Line { start, end } => vec![scale(start), scale(end)],
+        }
+    }
+
+    /// 点（相对包围盒左上角，未旋转）是否命中形状。
+    /// 闭合形状检测是否在内部，不闭合的形状检测到线条的距离是否在 tolerance 以内
+    pub fn contains(&self, (x, y): (f32, f32), width: f32, height: f32, corner_radius: f32, tolerance: f32) -> bool {
+        match self {
+            RgShape::Rectangle if corner_radius <= 0.0 => x >= 0.0 && x <= width && y >= 0.0 && y <= height,
+            RgShape::Ellipse => {
+                let (radius_x, radius_y) = (width / 2.0, height / 2.0);
+                if radius_x <= 0.0 || radius_y <= 0.0 {
+                    return false;
+                }
+                let (dx, dy) = ((x - radius_x) / radius_x, (y - radius_y) / radius_y);
+                dx * dx + dy * dy <= 1.0
+            }
+            shape if shape.is_closed() => point_in_polygon((x, y), &shape.outline(width, height, corner_radius)),
+            shape => shape.outline(width, height, corner_radius)
+                .windows(2)
+                .any(|segment| distance_to_segment((x, y), segment[0], segment[1]) <= tolerance),
+        }
+    }
+}
+
+// 圆角矩形的轮廓，没有圆角时即四个角
+fn rounded_rect_outline(width: f32, height: f32, radius: f32) -> Vec<(f32, f32)> {
+    if radius <= 0.0 {
+        return vec![(0.0, 0.0), (width, 0.0), (width, height), (0.0, height)];
+    }
+
+    let (left, top) = (radius, radius);
+    let (right, bottom) = (width - radius, height - radius);
+    // 圆角的圆心和起始角度（度），顺时针依次为左上、右上、右下、左下
+    let arcs = [((left, top), 180.0), ((right, top), 270.0), ((right, bottom), 0.0), ((left, bottom), 90.0)];
+
+    let mut points = Vec::with_capacity(arcs.len() * (CORNER_SEGMENTS + 1));
+    for ((arc_x, arc_y), start) in arcs {
+        for i in 0..=CORNER_SEGMENTS {
+            let angle = (start + 90.0 * i as f32 / CORNER_SEGMENTS as f32).to_radians();
+            points.push((arc_x + radius * angle.cos(), arc_y + radius * angle.sin()));
+        }
+    }
+    points
+}
+
+// 射线法判断点是否在多边形内部
+fn point_in_polygon((x, y): (f32, f32), polygon: &[(f32, f32)]) -> bool {
+    let mut inside = false;
+    for index in 0..polygon.len() {
+        let (x1, y1) = polygon[index];
+        let (x2, y2) = polygon[(index + 1) % polygon.len()];
+        if (y1 > y) != (y2 > y) && x < x1 + (y - y1) * (x2 - x1) / (y2 - y1) {
+            inside = !inside;
+        }
+    }
+    inside
+}
+
+// 点到线段 ab 的最短距离，形状和连线的命中测试共用
+pub(crate) fn distance_to_segment(point: (f32, f32), a: (f32, f32), b: (f32, f32)) -> f32 {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let length_squared = dx * dx + dy * dy;
+    let t = if length_squared == 0.0 {
+        0.0
+    } else {
+        (((point.0 - a.0) * dx + (point.1 - a.1) * dy) / length_squared).clamp(0.0, 1.0)
+    };
+    ((point.0 - a.0 - dx * t).powi(2) + (point.1 - a.1 - dy * t).powi(2)).sqrt()
+}
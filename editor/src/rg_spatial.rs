@@ -0,0 +1,320 @@
+use std::cell::Cell;
+use std::collections::HashMap;
+
+use crate::*;
+
+//************************************************************************** RgSpatialIndex **********************************************************************************//
+/// 基于均匀网格的空间索引，保存节点的世界坐标包围盒，用于点选、边缘命中和框选查询
+pub struct RgSpatialIndex {
+    cell_size: f32,
+    cells: HashMap<(i32, i32), Vec<u64>>,
+    bounds: HashMap<u64, RgGeometry>,
+    // 所有包围盒并集的缓存 (min_x, min_y, max_x, max_y)。删除或更新位于边界上的包围盒后失效，下次查询时重新计算
+    extent: Cell<Option<(f32, f32, f32, f32)>>,
+    extent_valid: Cell<bool>,
+}
+
+//************************************************************************** Trait **********************************************************************************//
+impl RgSpatialIndex {
+    pub fn new(cell_size: f32) -> Self {
+        Self {
+            cell_size: cell_size.max(1.0),
+            cells: HashMap::new(),
+            bounds: HashMap::new(),
+            extent: Cell::new(None),
+            extent_valid: Cell::new(true),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.bounds.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bounds.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.cells.clear();
+        self.bounds.clear();
+        self.extent.set(None);
+        self.extent_valid.set(true);
+    }
+
+    pub fn get(&self, id: u64) -> Option<RgGeometry> {
+        self.bounds.get(&id).copied()
+    }
+
+    /// 所有节点包围盒的并集，为空时返回 None
+    pub fn extent(&self) -> Option<RgGeometry> {
+        if !self.extent_valid.get() {
+            self.extent.set(self.bounds.values().map(|rect| corners(*rect)).reduce(merge));
+            self.extent_valid.set(true);
+        }
+        self.extent.get().map(|(min_x, min_y, max_x, max_y)| (min_x, min_y, max_x - min_x, max_y - min_y))
+    }
+
+    /// 插入或更新节点包围盒
+    pub fn insert(&mut self, id: u64, rect: RgGeometry) {
+        if let Some(old_rect) = self.bounds.get(&id).copied() {
+            if old_rect == rect {
+                return;
+            }
+            self.remove(id);
+        }
+
+        let (min_col, min_row, max_col, max_row) = self.cell_range(rect);
+        for col in min_col..=max_col {
+            for row in min_row..=max_row {
+                self.cells.entry((col, row)).or_default().push(id);
+            }
+        }
+        self.bounds.insert(id, rect);
+
+        if self.extent_valid.get() {
+            self.extent.set(Some(self.extent.get().map_or(corners(rect), |extent| merge(extent, corners(rect)))));
+        }
+    }
+
+    pub fn remove(&mut self, id: u64) -> Option<RgGeometry> {
+        let rect = self.bounds.remove(&id)?;
+        if self.extent.get().is_none_or(|extent| !inside(rect, extent)) {
+            self.extent_valid.set(false);
+        }
+
+        let (min_col, min_row, max_col, max_row) = self.cell_range(rect);
+        for col in min_col..=max_col {
+            for row in min_row..=max_row {
+                if let Some(ids) = self.cells.get_mut(&(col, row)) {
+                    ids.retain(|other| *other != id);
+                    if ids.is_empty() {
+                        self.cells.remove(&(col, row));
+                    }
+                }
+            }
+        }
+
+        Some(rect)
+    }
+
+    /// 查询与矩形（世界坐标）相交的节点，结果无序
+    pub fn query_rect(&self, rect: RgGeometry) -> Vec<u64> {
+        let (x, y, width, height) = rect;
+        self.collect_candidates(rect, |bounds| {
+            bounds.0 < x + width
+                && bounds.0 + bounds.2 > x
+                && bounds.1 < y + height
+                && bounds.1 + bounds.3 > y
+        })
+    }
+
+    /// 查询包围盒向外扩展 margin 后包含该点的节点，结果无序
+    pub fn query_point(&self, x: f32, y: f32, margin: f32) -> Vec<u64> {
+        let rect = (x - margin, y - margin, margin * 2.0, margin * 2.0);
+        self.collect_candidates(rect, |bounds| {
+            x >= bounds.0 - margin
+                && x <= bounds.0 + bounds.2 + margin
+                && y >= bounds.1 - margin
+                && y <= bounds.1 + bounds.3 + margin
+        })
+    }
+
+    fn collect_candidates(&self, rect: RgGeometry, filter: impl Fn(RgGeometry) -> bool) -> Vec<u64> {
+        let (min_col, min_row, max_col, max_row) = self.cell_range(rect);
+        let mut result = Vec::new();
+
+        for col in min_col..=max_col {
+            for row in min_row..=max_row {
+                let Some(ids) = self.cells.get(&(col, row)) else {
+                    continue;
+                };
+                for id in ids {
+                    if let Some(bounds) = self.bounds.get(id) {
+                        if filter(*bounds) {
+                            result.push(*id);
+                        }
+                    }
+                }
+            }
+        }
+
+        // 跨越多个格子的节点会被重复收集
+        result.sort_unstable();
+        result.dedup();
+        result
+    }
+
+    fn cell_range(&self, rect: RgGeometry) -> (i32, i32, i32, i32) {
+        let (x, y, width, height) = rect;
+        let min_col = (x.min(x + width) / self.cell_size).floor() as i32;
+        let min_row = (y.min(y + height) / self.cell_size).floor() as i32;
+        let max_col = (x.max(x + width) / self.cell_size).floor() as i32;
+        let max_row = (y.max(y + height) / self.cell_size).floor() as i32;
+        (min_col, min_row, max_col, max_row)
+    }
+}
+
+impl Default for RgSpatialIndex {
+    fn default() -> Self {
+        Self::new(256.0)
+    }
+}
+
+fn corners((x, y, width, height): RgGeometry) -> (f32, f32, f32, f32) {
+    (x, y, x + width, y + height)
+}
+
+fn merge(a: (f32, f32, f32, f32), b: (f32, f32, f32, f32)) -> (f32, f32, f32, f32) {
+    (a.0.min(b.0), a.1.min(b.1), a.2.max(b.2), a.3.max(b.3))
+}
+
+// rect 是否在 extent 内部且不接触边界，删除这样的包围盒不会改变并集
+fn inside(rect: RgGeometry, extent: (f32, f32, f32, f32)) -> bool {
+    let (min_x, min_y, max_x, max_y) = corners(rect);
+    min_x > extent.0 && min_y > extent.1 && max_x < extent.2 && max_y < extent.3
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    use super::*;
+
+    fn intersects(a: RgGeometry, b: RgGeometry) -> bool {
+        a.0 < b.0 + b.2 && a.0 + a.2 > b.0 && a.1 < b.1 + b.3 && a.1 + a.3 > b.1
+    }
+
+    fn contains_point(rect: RgGeometry, (x, y): (f32, f32), margin: f32) -> bool {
+        x >= rect.0 - margin && x <= rect.0 + rect.2 + margin && y >= rect.1 - margin && y <= rect.1 + rect.3 + margin
+    }
+
+    fn brute_rect(rects: &HashMap<u64, RgGeometry>, query: RgGeometry) -> Vec<u64> {
+        let mut ids: Vec<u64> = rects.iter().filter(|(_, rect)| intersects(**rect, query)).map(|(id, _)| *id).collect();
+        ids.sort_unstable();
+        ids
+    }
+
+    fn brute_point(rects: &HashMap<u64, RgGeometry>, point: (f32, f32), margin: f32) -> Vec<u64> {
+        let mut ids: Vec<u64> = rects.iter().filter(|(_, rect)| contains_point(**rect, point, margin)).map(|(id, _)| *id).collect();
+        ids.sort_unstable();
+        ids
+    }
+
+    fn random_rect(rng: &mut StdRng) -> RgGeometry {
+        (
+            rng.gen_range(-2000.0..2000.0),
+            rng.gen_range(-2000.0..2000.0),
+            rng.gen_range(1.0..600.0),
+            rng.gen_range(1.0..600.0),
+        )
+    }
+
+    // 与线性扫描的结果比较，覆盖负坐标、跨越多个格子的矩形、更新和删除
+    #[test]
+    fn queries_match_linear_scan() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut index = RgSpatialIndex::new(128.0);
+        let mut rects = HashMap::new();
+
+        for id in 0..300 {
+            let rect = random_rect(&mut rng);
+            index.insert(id, rect);
+            rects.insert(id, rect);
+        }
+        for id in 0..100 {
+            let rect = random_rect(&mut rng);
+            index.insert(id, rect);
+            rects.insert(id, rect);
+        }
+        for id in 100..150 {
+            assert_eq!(index.remove(id), rects.remove(&id));
+        }
+        assert_eq!(index.len(), rects.len());
+
+        for _ in 0..200 {
+            let query = random_rect(&mut rng);
+            let mut result = index.query_rect(query);
+            result.sort_unstable();
+            assert_eq!(result, brute_rect(&rects, query));
+
+            let point = (rng.gen_range(-2500.0..2500.0), rng.gen_range(-2500.0..2500.0));
+            let mut result = index.query_point(point.0, point.1, 8.0);
+            result.sort_unstable();
+            assert_eq!(result, brute_point(&rects, point, 8.0));
+        }
+    }
+
+    #[test]
+    fn insert_reindexes_updated_rect() {
+        let mut index = RgSpatialIndex::new(100.0);
+        index.insert(1, (-250.0, -250.0, 50.0, 50.0));
+        index.insert(1, (300.0, 300.0, 250.0, 250.0));
+
+        assert!(index.query_point(-225.0, -225.0, 0.0).is_empty());
+        assert_eq!(index.query_point(500.0, 500.0, 0.0), vec![1]);
+        assert_eq!(index.query_rect((290.0, 290.0, 20.0, 20.0)), vec![1]);
+        assert_eq!(index.get(1), Some((300.0, 300.0, 250.0, 250.0)));
+        assert_eq!(index.len(), 1);
+    }
+
+    #[test]
+    fn remove_cleans_cells() {
+        let mut index = RgSpatialIndex::new(100.0);
+        index.insert(1, (-150.0, -150.0, 400.0, 400.0));
+        index.insert(2, (0.0, 0.0, 10.0, 10.0));
+
+        assert_eq!(index.remove(1), Some((-150.0, -150.0, 400.0, 400.0)));
+        assert_eq!(index.remove(1), None);
+        assert_eq!(index.query_rect((-1000.0, -1000.0, 2000.0, 2000.0)), vec![2]);
+        assert_eq!(index.cells.len(), 1);
+
+        index.remove(2);
+        assert!(index.is_empty());
+        assert!(index.cells.is_empty());
+    }
+
+    #[test]
+    fn extent_covers_all_rects() {
+        let mut index = RgSpatialIndex::default();
+        assert_eq!(index.extent(), None);
+
+        index.insert(1, (-100.0, 50.0, 20.0, 30.0));
+        index.insert(2, (400.0, -20.0, 100.0, 10.0));
+        assert_eq!(index.extent(), Some((-100.0, -20.0, 600.0, 100.0)));
+
+        // 更新或删除边界上的包围盒后缩小，删除内部的包围盒不变
+        index.insert(3, (0.0, 0.0, 10.0, 10.0));
+        index.insert(2, (100.0, 0.0, 10.0, 10.0));
+        assert_eq!(index.extent(), Some((-100.0, 0.0, 210.0, 80.0)));
+        index.remove(3);
+        assert_eq!(index.extent(), Some((-100.0, 0.0, 210.0, 80.0)));
+        index.remove(1);
+        assert_eq!(index.extent(), Some((100.0, 0.0, 10.0, 10.0)));
+        index.remove(2);
+        assert_eq!(index.extent(), None);
+    }
+
+    #[test]
+    fn cached_extent_matches_linear_scan() {
+        let mut rng = StdRng::seed_from_u64(11);
+        let mut index = RgSpatialIndex::new(128.0);
+        let mut rects = HashMap::new();
+
+        for step in 0..500 {
+            let id = rng.gen_range(0..60);
+            if step % 3 == 0 {
+                assert_eq!(index.remove(id), rects.remove(&id));
+            } else {
+                let rect = random_rect(&mut rng);
+                index.insert(id, rect);
+                rects.insert(id, rect);
+            }
+            let expected = rects.values()
+                .map(|rect| corners(*rect))
+                .reduce(merge)
+                .map(|(min_x, min_y, max_x, max_y)| (min_x, min_y, max_x - min_x, max_y - min_y));
+            assert_eq!(index.extent(), expected);
+        }
+    }
+}
@@ -0,0 +1,102 @@
+use gpui::*;
+use serde::{Deserialize, Serialize};
+
+//************************************************************************** RgStyle **********************************************************************************//
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RgStrokeDash {
+    #[default]
+    Solid,
+    Dashed,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RgTextAlign {
+    Left,
+    #[default]
+    Center,
+    Right,
+}
+
+/// 投影，偏移和模糊半径为世界坐标
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RgShadow {
+    /// 0xRRGGBBAA
+    pub color: u32,
+    pub offset_x: f32,
+    pub offset_y: f32,
+    pub blur: f32,
+}
+
+/// 节点的外观，颜色均为 0xRRGGBBAA，尺寸均为世界坐标（随缩放变化）
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RgStyle {
+    pub fill_color: u32,
+    /// 与 fill_color 的透明度相乘，0.0 ~ 1.0
+    pub fill_opacity: f32,
+    pub stroke_color: u32,
+    /// 为 0 时不绘制边框
+    pub stroke_width: f32,
+    pub stroke_dash: RgStrokeDash,
+    pub corner_radius: f32,
+    pub shadow: Option<RgShadow>,
+    pub text_color: u32,
+    pub text_size: f32,
+    pub text_align: RgTextAlign,
+}
+
+//************************************************************************** Trait **********************************************************************************//
+impl RgStyle {
+    pub fn fill(&self) -> Rgba {
+        let mut color = rgba(self.fill_color);
+        color.a *= self.fill_opacity.clamp(0.0, 1.0);
+        color
+    }
+
+    pub fn stroke(&self) -> Rgba {
+        rgba(self.stroke_color)
+    }
+
+    pub fn text(&self) -> Rgba {
+        rgba(self.text_color)
+    }
+
+    /// 圆角半径不超过短边的一半
+    pub fn clamped_corner_radius(&self, width: f32, height: f32) -> f32 {
+        self.corner_radius.clamp(0.0, width.abs().min(height.abs()) / 2.0)
+    }
+}
+
+impl Default for RgStyle {
+    fn default() -> Self {
+        Self {
+            fill_color: 0xf0f0f0aa,
+            fill_opacity: 1.0,
+            stroke_color: 0x505050ff,
+            stroke_width: 1.0,
+            stroke_dash: RgStrokeDash::Solid,
+            corner_radius: 0.0,
+            shadow: None,
+            text_color: 0x000000ff,
+            text_size: 14.0,
+            text_align: RgTextAlign::Center,
+        }
+    }
+}
+
+impl RgShadow {
+    pub fn color(&self) -> Rgba {
+        rgba(self.color)
+    }
+}
+
+impl Default for RgShadow {
+    fn default() -> Self {
+        Self {
+            color: 0x00000040,
+            offset_x: 0.0,
+            offset_y: 4.0,
+            blur: 8.0,
+        }
+    }
+}
@@ -0,0 +1,817 @@
+use std::ops::Range;
+
+use gpui::*;
+use crate::*;
+
+actions!(
+    rg_text_edit,
+    [
+        TextBackspace,
+        TextDelete,
+        TextLeft,
+        TextRight,
+        TextUp,
+        TextDown,
+        TextSelectLeft,
+        TextSelectRight,
+        TextSelectUp,
+        TextSelectDown,
+        TextSelectAll,
+        TextHome,
+        TextEnd,
+        TextCopy,
+        TextCut,
+        TextPaste,
+        CommitTextEdit,
+        CancelTextEdit,
+    ]
+);
+
+//************************************************************************** RgTextEditor **********************************************************************************//
+// 文字与节点边框之间的内边距（屏幕像素，随缩放放大），与 RgRect 的标签一致
+pub const LABEL_PADDING: f32 = 4.0;
+const LINE_HEIGHT_SCALE: f32 = 1.2;
+
+/// 结束编辑的请求，由 RgEditor 处理
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RgTextEditEvent {
+    Commit,
+    Cancel,
+}
+
+/// 节点标签的原位编辑器，支持光标、选择、输入法和剪贴板。文字按节点宽度折行，不允许换行符
+pub struct RgTextEditor {
+    pub node_id: u64,
+    /// 节点样式和缩放，由 RgEditor 在每次渲染前同步
+    pub style: RgStyle,
+    pub zoom: f32,
+    focus_handle: FocusHandle,
+    content: String,
+    original: String,
+    selected_range: Range<usize>,
+    selection_reversed: bool,
+    marked_range: Option<Range<usize>>,
+    last_layout: Option<WrappedLine>,
+    last_bounds: Option<Bounds<Pixels>>,
+    is_selecting: bool,
+}
+
+struct RgTextElement {
+    editor: Entity<RgTextEditor>,
+}
+
+struct RgTextPrepaint {
+    layout: Option<WrappedLine>,
+    selections: Vec<PaintQuad>,
+    cursor: Option<PaintQuad>,
+}
+
+//************************************************************************** Trait **********************************************************************************//
+impl RgTextEditor {
+    /// 开始编辑时选中全部文字
+    pub fn new(node_id: u64, label: &str, style: RgStyle, zoom: f32, cx: &mut Context<Self>) -> Self {
+        let content = single_line(label);
+        Self {
+            node_id,
+            style,
+            zoom,
+            focus_handle: cx.focus_handle(),
+            selected_range: 0..content.len(),
+            original: content.clone(),
+            content,
+            selection_reversed: false,
+            marked_range: None,
+            last_layout: None,
+            last_bounds: None,
+            is_selecting: false,
+        }
+    }
+
+    pub fn bind_keys(cx: &mut App) {
+        cx.bind_keys([
+            KeyBinding::new("backspace", TextBackspace, Some("RgTextEditor")),
+            KeyBinding::new("delete", TextDelete, Some("RgTextEditor")),
+            KeyBinding::new("left", TextLeft, Some("RgTextEditor")),
+            KeyBinding::new("right", TextRight, Some("RgTextEditor")),
+            KeyBinding::new("up", TextUp, Some("RgTextEditor")),
+            KeyBinding::new("down", TextDown, Some("RgTextEditor")),
+            KeyBinding::new("shift-left", TextSelectLeft, Some("RgTextEditor")),
+            KeyBinding::new("shift-right", TextSelectRight, Some("RgTextEditor")),
+            KeyBinding::new("shift-up", TextSelectUp, Some("RgTextEditor")),
+            KeyBinding::new("shift-down", TextSelectDown, Some("RgTextEditor")),
+            KeyBinding::new("secondary-a", TextSelectAll, Some("RgTextEditor")),
+            KeyBinding::new("home", TextHome, Some("RgTextEditor")),
+            KeyBinding::new("end", TextEnd, Some("RgTextEditor")),
+            KeyBinding::new("secondary-c", TextCopy, Some("RgTextEditor")),
+            KeyBinding::new("secondary-x", TextCut, Some("RgTextEditor")),
+            KeyBinding::new("secondary-v", TextPaste, Some("RgTextEditor")),
+            KeyBinding::new("enter", CommitTextEdit, Some("RgTextEditor")),
+            KeyBinding::new("escape", CancelTextEdit, Some("RgTextEditor")),
+        ]);
+
+        // 编辑文字时屏蔽 RgEditor 中其余的快捷键，避免输入时误操作节点
+        cx.bind_keys([
+            "tab",
+            "shift-tab",
+            "secondary-z",
+            "secondary-shift-z",
+            "secondary-shift-v",
+            "secondary-d",
+            "secondary-g",
+            "secondary-shift-g",
+            "alt-a",
+            "alt-h",
+            "alt-d",
+            "alt-w",
+            "alt-v",
+            "alt-s",
+            "alt-shift-h",
+            "alt-shift-v",
+            "secondary-alt-h",
+            "secondary-alt-v",
+        ]
+        .map(|keystroke| KeyBinding::new(keystroke, NoAction, Some("RgTextEditor"))));
+    }
+
+    pub fn content(&self) -> &str {
+        &self.content
+    }
+
+    /// 文字是否与开始编辑时不同
+    pub fn is_modified(&self) -> bool {
+        self.content != self.original
+    }
+
+    pub fn focus(&self, window: &mut Window) {
+        window.focus(&self.focus_handle);
+    }
+
+    //****************************** 光标移动 ****************************//
+    fn on_backspace(&mut self, _: &TextBackspace, window: &mut Window, cx: &mut Context<Self>) {
+        if self.selected_range.is_empty() {
+            self.select_to(self.previous_boundary(self.cursor_offset()), cx);
+        }
+        self.replace_text_in_range(None, "", window, cx);
+    }
+
+    fn on_delete(&mut self, _: &TextDelete, window: &mut Window, cx: &mut Context<Self>) {
+        if self.selected_range.is_empty() {
+            self.select_to(self.next_boundary(self.cursor_offset()), cx);
+        }
+        self.replace_text_in_range(None, "", window, cx);
+    }
+
+    fn on_left(&mut self, _: &TextLeft, _window: &mut Window, cx: &mut Context<Self>) {
+        if self.selected_range.is_empty() {
+            self.move_to(self.previous_boundary(self.cursor_offset()), cx);
+        } else {
+            self.move_to(self.selected_range.start, cx);
+        }
+    }
+
+    fn on_right(&mut self, _: &TextRight, _window: &mut Window, cx: &mut Context<Self>) {
+        if self.selected_range.is_empty() {
+            self.move_to(self.next_boundary(self.cursor_offset()), cx);
+        } else {
+            self.move_to(self.selected_range.end, cx);
+        }
+    }
+
+    fn on_up(&mut self, _: &TextUp, _window: &mut Window, cx: &mut Context<Self>) {
+        self.move_to(self.vertical_offset(-1), cx);
+    }
+
+    fn on_down(&mut self, _: &TextDown, _window: &mut Window, cx: &mut Context<Self>) {
+        self.move_to(self.vertical_offset(1), cx);
+    }
+
+    fn on_select_left(&mut self, _: &TextSelectLeft, _window: &mut Window, cx: &mut Context<Self>) {
+        self.select_to(self.previous_boundary(self.cursor_offset()), cx);
+    }
+
+    fn on_select_right(&mut self, _: &TextSelectRight, _window: &mut Window, cx: &mut Context<Self>) {
+        self.select_to(self.next_boundary(self.cursor_offset()), cx);
+    }
+
+    fn on_select_up(&mut self, _: &TextSelectUp, _window: &mut Window, cx: &mut Context<Self>) {
+        self.select_to(self.vertical_offset(-1), cx);
+    }
+
+    fn on_select_down(&mut self, _: &TextSelectDown, _window: &mut Window, cx: &mut Context<Self>) {
+        self.select_to(self.vertical_offset(1), cx);
+    }
+
+    fn on_select_all(&mut self, _: &TextSelectAll, _window: &mut Window, cx: &mut Context<Self>) {
+        self.move_to(0, cx);
+        self.select_to(self.content.len(), cx);
+    }
+
+    fn on_home(&mut self, _: &TextHome, _window: &mut Window, cx: &mut Context<Self>) {
+        self.move_to(0, cx);
+    }
+
+    fn on_end(&mut self, _: &TextEnd, _window: &mut Window, cx: &mut Context<Self>) {
+        self.move_to(self.content.len(), cx);
+    }
+
+    //****************************** 剪贴板 ****************************//
+    fn on_copy(&mut self, _: &TextCopy, _window: &mut Window, cx: &mut Context<Self>) {
+        if !self.selected_range.is_empty() {
+            cx.write_to_clipboard(ClipboardItem::new_string(self.content[self.selected_range.clone()].to_string()));
+        }
+    }
+
+    fn on_cut(&mut self, _: &TextCut, window: &mut Window, cx: &mut Context<Self>) {
+        if !self.selected_range.is_empty() {
+            cx.write_to_clipboard(ClipboardItem::new_string(self.content[self.selected_range.clone()].to_string()));
+            self.replace_text_in_range(None, "", window, cx);
+        }
+    }
+
+    fn on_paste(&mut self, _: &TextPaste, window: &mut Window, cx: &mut Context<Self>) {
+        if let Some(text) = cx.read_from_clipboard().and_then(|item| item.text()) {
+            self.replace_text_in_range(None, &single_line(&text), window, cx);
+        }
+    }
+
+    fn on_commit(&mut self, _: &CommitTextEdit, _window: &mut Window, cx: &mut Context<Self>) {
+        cx.emit(RgTextEditEvent::Commit);
+    }
+
+    fn on_cancel(&mut self, _: &CancelTextEdit, _window: &mut Window, cx: &mut Context<Self>) {
+        cx.emit(RgTextEditEvent::Cancel);
+    }
+
+    //****************************** 鼠标 ****************************//
+    fn on_mouse_down(&mut self, event: &MouseDownEvent, window: &mut Window, cx: &mut Context<Self>) {
+        // 编辑框内的点击不交给 RgEditor 处理
+        cx.stop_propagation();
+        window.focus(&self.focus_handle);
+
+        let index = self.index_for_position(event.position);
+        match event.click_count {
+            // 双击选中单词，三击选中全部
+            2 => {
+                let (start, end) = self.word_range(index);
+                self.move_to(start, cx);
+                self.select_to(end, cx);
+            }
+            count if count >= 3 => {
+                self.move_to(0, cx);
+                self.select_to(self.content.len(), cx);
+            }
+            _ => {
+                self.is_selecting = true;
+                if event.modifiers.shift {
+                    self.select_to(index, cx);
+                } else {
+                    self.move_to(index, cx);
+                }
+            }
+        }
+    }
+
+    fn on_mouse_up(&mut self, _: &MouseUpEvent, _window: &mut Window, _cx: &mut Context<Self>) {
+        self.is_selecting = false;
+    }
+
+    fn on_mouse_move(&mut self, event: &MouseMoveEvent, _window: &mut Window, cx: &mut Context<Self>) {
+        if self.is_selecting {
+            self.select_to(self.index_for_position(event.position), cx);
+        }
+    }
+
+    //****************************** 选择 ****************************//
+    fn move_to(&mut self, offset: usize, cx: &mut Context<Self>) {
+        self.selected_range = offset..offset;
+        self.selection_reversed = false;
+        cx.notify();
+    }
+
+    fn select_to(&mut self, offset: usize, cx: &mut Context<Self>) {
+        if self.selection_reversed {
+            self.selected_range.start = offset;
+        } else {
+            self.selected_range.end = offset;
+        }
+        if self.selected_range.end < self.selected_range.start {
+            self.selection_reversed = !self.selection_reversed;
+            self.selected_range = self.selected_range.end..self.selected_range.start;
+        }
+        cx.notify();
+    }
+
+    fn cursor_offset(&self) -> usize {
+        if self.selection_reversed {
+            self.selected_range.start
+        } else {
+            self.selected_range.end
+        }
+    }
+
+    fn previous_boundary(&self, offset: usize) -> usize {
+        self.content[..offset].char_indices().next_back().map_or(0, |(index, _)| index)
+    }
+
+    fn next_boundary(&self, offset: usize) -> usize {
+        self.content[offset..].chars().next().map_or(self.content.len(), |c| offset + c.len_utf8())
+    }
+
+    // index 所在单词的范围，单词由字母数字组成
+    fn word_range(&self, index: usize) -> (usize, usize) {
+        let is_word = |c: char| c.is_alphanumeric() || c == '_';
+        let start = self.content[..index]
+            .char_indices()
+            .rev()
+            .take_while(|(_, c)| is_word(*c))
+            .last()
+            .map_or(index, |(i, _)| i);
+        let end = self.content[index..]
+            .char_indices()
+            .find(|(_, c)| !is_word(*c))
+            .map_or(self.content.len(), |(i, _)| index + i);
+        (start, end)
+    }
+
+    // 光标移到上一行或下一行中水平位置最接近的地方
+    fn vertical_offset(&self, direction: i32) -> usize {
+        let cursor = self.cursor_offset();
+        let (Some(layout), Some(bounds)) = (self.last_layout.as_ref(), self.last_bounds) else {
+            return cursor;
+        };
+        let line_height = self.line_height();
+        let Some(position) = caret_position(layout, cursor, bounds, line_height, self.style.text_align) else {
+            return cursor;
+        };
+
+        let target = point(position.x, position.y + line_height * (0.5 + direction as f32));
+        let lines = visual_lines(layout);
+        let origin = text_origin(lines.len(), bounds, line_height);
+        if target.y < origin.y {
+            return 0;
+        }
+        if target.y > origin.y + line_height * lines.len() as f32 {
+            return self.content.len();
+        }
+        self.index_for_position(target)
+    }
+
+    // 屏幕坐标对应的字节下标
+    fn index_for_position(&self, position: Point<Pixels>) -> usize {
+        let (Some(layout), Some(bounds)) = (self.last_layout.as_ref(), self.last_bounds) else {
+            return 0;
+        };
+        let line_height = self.line_height();
+        let lines = visual_lines(layout);
+        let origin = text_origin(lines.len(), bounds, line_height);
+
+        let row = ((position.y - origin.y) / line_height).floor().clamp(0.0, (lines.len() - 1) as f32) as usize;
+        let offset = line_offset(layout, &lines[row], bounds.size.width, line_height, self.style.text_align);
+        let x = (position.x - origin.x - offset).max(px(0.0));
+        let y = line_height * (row as f32 + 0.5);
+
+        let index = layout.closest_index_for_position(point(x, y), line_height).unwrap_or_else(|index| index);
+        index.clamp(lines[row].start, lines[row].end)
+    }
+
+    fn font_size(&self) -> Pixels {
+        px(self.style.text_size * self.zoom)
+    }
+
+    fn line_height(&self) -> Pixels {
+        self.font_size() * LINE_HEIGHT_SCALE
+    }
+
+    //****************************** UTF-16 ****************************//
+    fn offset_from_utf16(&self, offset: usize) -> usize {
+        let mut utf8_offset = 0;
+        let mut utf16_count = 0;
+        for c in self.content.chars() {
+            if utf16_count >= offset {
+                break;
+            }
+            utf16_count += c.len_utf16();
+            utf8_offset += c.len_utf8();
+        }
+        utf8_offset
+    }
+
+    fn offset_to_utf16(&self, offset: usize) -> usize {
+        let mut utf16_offset = 0;
+        let mut utf8_count = 0;
+        for c in self.content.chars() {
+            if utf8_count >= offset {
+                break;
+            }
+            utf8_count += c.len_utf8();
+            utf16_offset += c.len_utf16();
+        }
+        utf16_offset
+    }
+
+    fn range_to_utf16(&self, range: &Range<usize>) -> Range<usize> {
+        self.offset_to_utf16(range.start)..self.offset_to_utf16(range.end)
+    }
+
+    fn range_from_utf16(&self, range_utf16: &Range<usize>) -> Range<usize> {
+        self.offset_from_utf16(range_utf16.start)..self.offset_from_utf16(range_utf16.end)
+    }
+}
+
+impl EventEmitter<RgTextEditEvent> for RgTextEditor {}
+
+impl Focusable for RgTextEditor {
+    fn focus_handle(&self, _: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl EntityInputHandler for RgTextEditor {
+    fn text_for_range(
+        &mut self,
+        range_utf16: Range<usize>,
+        actual_range: &mut Option<Range<usize>>,
+        _window: &mut Window,
+        _cx: &mut Context<Self>,
+    ) -> Option<String> {
+        let range = self.range_from_utf16(&range_utf16);
+        actual_range.replace(self.range_to_utf16(&range));
+        Some(self.content[range].to_string())
+    }
+
+    fn selected_text_range(
+        &mut self,
+        _ignore_disabled_input: bool,
+        _window: &mut Window,
+        _cx: &mut Context<Self>,
+    ) -> Option<UTF16Selection> {
+        Some(UTF16Selection {
+            range: self.range_to_utf16(&self.selected_range),
+            reversed: self.selection_reversed,
+        })
+    }
+
+    fn marked_text_range(&self, _window: &mut Window, _cx: &mut Context<Self>) -> Option<Range<usize>> {
+        self.marked_range.as_ref().map(|range| self.range_to_utf16(range))
+    }
+
+    fn unmark_text(&mut self, _window: &mut Window, _cx: &mut Context<Self>) {
+        self.marked_range = None;
+    }
+
+    fn replace_text_in_range(
+        &mut self,
+        range_utf16: Option<Range<usize>>,
+        new_text: &str,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let range = range_utf16
+            .as_ref()
+            .map(|range_utf16| self.range_from_utf16(range_utf16))
+            .or(self.marked_range.clone())
+            .unwrap_or(self.selected_range.clone());
+        let new_text = single_line(new_text);
+
+        self.content.replace_range(range.clone(), &new_text);
+        self.selected_range = range.start + new_text.len()..range.start + new_text.len();
+        self.selection_reversed = false;
+        self.marked_range = None;
+        cx.notify();
+    }
+
+    fn replace_and_mark_text_in_range(
+        &mut self,
+        range_utf16: Option<Range<usize>>,
+        new_text: &str,
+        new_selected_range_utf16: Option<Range<usize>>,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let range = range_utf16
+            .as_ref()
+            .map(|range_utf16| self.range_from_utf16(range_utf16))
+            .or(self.marked_range.clone())
+            .unwrap_or(self.selected_range.clone());
+        let new_text = single_line(new_text);
+
+        self.content.replace_range(range.clone(), &new_text);
+        self.marked_range = (!new_text.is_empty()).then(|| range.start..range.start + new_text.len());
+        // 输入法给出的选择范围相对于标记文字
+        self.selected_range = new_selected_range_utf16
+            .as_ref()
+            .map(|range_utf16| {
+                let marked = &new_text[..];
+                let start = utf16_to_utf8(marked, range_utf16.start);
+                let end = utf16_to_utf8(marked, range_utf16.end);
+                range.start + start..range.start + end
+            })
+            .unwrap_or_else(|| range.start + new_text.len()..range.start + new_text.len());
+        self.selection_reversed = false;
+        cx.notify();
+    }
+
+    fn bounds_for_range(
+        &mut self,
+        range_utf16: Range<usize>,
+        _element_bounds: Bounds<Pixels>,
+        _window: &mut Window,
+        _cx: &mut Context<Self>,
+    ) -> Option<Bounds<Pixels>> {
+        let layout = self.last_layout.as_ref()?;
+        let bounds = self.last_bounds?;
+        let line_height = self.line_height();
+        let range = self.range_from_utf16(&range_utf16);
+
+        let start = caret_position(layout, range.start, bounds, line_height, self.style.text_align)?;
+        let end = caret_position(layout, range.end, bounds, line_height, self.style.text_align)?;
+        Some(Bounds::from_corners(start, point(end.x.max(start.x), end.y + line_height)))
+    }
+
+    fn character_index_for_point(
+        &mut self,
+        point: Point<Pixels>,
+        _window: &mut Window,
+        _cx: &mut Context<Self>,
+    ) -> Option<usize> {
+        self.last_layout.as_ref()?;
+        Some(self.offset_to_utf16(self.index_for_position(point)))
+    }
+}
+
+impl IntoElement for RgTextElement {
+    type Element = Self;
+
+    fn into_element(self) -> Self::Element {
+        self
+    }
+}
+
+impl Element for RgTextElement {
+    type RequestLayoutState = ();
+    type PrepaintState = RgTextPrepaint;
+
+    fn id(&self) -> Option<ElementId> {
+        None
+    }
+
+    fn source_location(&self) -> Option<&'static core::panic::Location<'static>> {
+        None
+    }
+
+    fn request_layout(
+        &mut self,
+        _id: Option<&GlobalElementId>,
+        _inspector_id: Option<&InspectorElementId>,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> (LayoutId, Self::RequestLayoutState) {
+        let mut style = Style::default();
+        style.size.width = relative(1.0).into();
+        style.size.height = relative(1.0).into();
+        (window.request_layout(style, [], cx), ())
+    }
+
+    fn prepaint(
+        &mut self,
+        _id: Option<&GlobalElementId>,
+        _inspector_id: Option<&InspectorElementId>,
+        bounds: Bounds<Pixels>,
+        _request_layout: &mut Self::RequestLayoutState,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Self::PrepaintState {
+        let editor = self.editor.read(cx);
+        let content = editor.content.clone();
+        let font_size = editor.font_size();
+        let line_height = editor.line_height();
+        let align = editor.style.text_align;
+        let selected_range = editor.selected_range.clone();
+        let cursor = editor.cursor_offset();
+
+        // 输入法的标记文字加下划线
+        let run = TextRun {
+            len: content.len(),
+            font: window.text_style().font(),
+            color: editor.style.text().into(),
+            background_color: None,
+            underline: None,
+            strikethrough: None,
+        };
+        let runs: Vec<TextRun> = match editor.marked_range.as_ref() {
+            Some(marked_range) => vec![
+                TextRun { len: marked_range.start, ..run.clone() },
+                TextRun {
+                    len: marked_range.end - marked_range.start,
+                    underline: Some(UnderlineStyle {
+                        color: Some(run.color),
+                        thickness: px(1.0),
+                        wavy: false,
+                    }),
+                    ..run.clone()
+                },
+                TextRun { len: content.len() - marked_range.end, ..run },
+            ]
+            .into_iter()
+            .filter(|run| run.len > 0)
+            .collect(),
+            None => vec![run],
+        };
+
+        let layout = window.text_system()
+            .shape_text(SharedString::from(content), font_size, &runs, Some(bounds.size.width), None)
+            .ok()
+            .and_then(|lines| lines.into_iter().next());
+        let Some(layout) = layout else {
+            return RgTextPrepaint { layout: None, selections: Vec::new(), cursor: None };
+        };
+
+        let selection_color = rgba(0x008aff40);
+        let mut selections = Vec::new();
+        let mut cursor_quad = None;
+        if selected_range.is_empty() {
+            if let Some(position) = caret_position(&layout, cursor, bounds, line_height, align) {
+                cursor_quad = Some(fill(Bounds::new(position, size(px(1.5), line_height)), rgb(0x008aff)));
+            }
+        } else {
+            // 选择范围按折行拆成多段
+            for line in visual_lines(&layout) {
+                let start = selected_range.start.max(line.start);
+                let end = selected_range.end.min(line.end);
+                if start >= end {
+                    continue;
+                }
+                let (Some(from), Some(to)) = (
+                    position_in_line(&layout, start, &line, bounds, line_height, align),
+                    position_in_line(&layout, end, &line, bounds, line_height, align),
+                ) else {
+                    continue;
+                };
+                selections.push(fill(Bounds::from_corners(from, point(to.x, to.y + line_height)), selection_color));
+            }
+        }
+
+        RgTextPrepaint {
+            layout: Some(layout),
+            selections,
+            cursor: cursor_quad,
+        }
+    }
+
+    fn paint(
+        &mut self,
+        _id: Option<&GlobalElementId>,
+        _inspector_id: Option<&InspectorElementId>,
+        bounds: Bounds<Pixels>,
+        _request_layout: &mut Self::RequestLayoutState,
+        prepaint: &mut Self::PrepaintState,
+        window: &mut Window,
+        cx: &mut App,
+    ) {
+        let (focus_handle, line_height, align) = {
+            let editor = self.editor.read(cx);
+            (editor.focus_handle.clone(), editor.line_height(), editor.style.text_align)
+        };
+        window.handle_input(&focus_handle, ElementInputHandler::new(bounds, self.editor.clone()), cx);
+
+        for selection in prepaint.selections.drain(..) {
+            window.paint_quad(selection);
+        }
+
+        let Some(layout) = prepaint.layout.take() else {
+            return;
+        };
+        let origin = text_origin(visual_lines(&layout).len(), bounds, line_height);
+        let text_align = match align {
+            RgTextAlign::Left => TextAlign::Left,
+            RgTextAlign::Center => TextAlign::Center,
+            RgTextAlign::Right => TextAlign::Right,
+        };
+        let text_bounds = Bounds::new(origin, size(bounds.size.width, bounds.size.height));
+        layout.paint(origin, line_height, text_align, Some(text_bounds), window, cx).ok();
+
+        if focus_handle.is_focused(window) {
+            if let Some(cursor) = prepaint.cursor.take() {
+                window.paint_quad(cursor);
+            }
+        }
+
+        self.editor.update(cx, |editor, _| {
+            editor.last_layout = Some(layout);
+            editor.last_bounds = Some(bounds);
+        });
+    }
+}
+
+//************************************************************************** Render **********************************************************************************//
+impl Render for RgTextEditor {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        div()
+            .size_full()
+            .key_context("RgTextEditor")
+            .track_focus(&self.focus_handle)
+            .cursor(CursorStyle::IBeam)
+            .on_action(cx.listener(Self::on_backspace))
+            .on_action(cx.listener(Self::on_delete))
+            .on_action(cx.listener(Self::on_left))
+            .on_action(cx.listener(Self::on_right))
+            .on_action(cx.listener(Self::on_up))
+            .on_action(cx.listener(Self::on_down))
+            .on_action(cx.listener(Self::on_select_left))
+            .on_action(cx.listener(Self::on_select_right))
+            .on_action(cx.listener(Self::on_select_up))
+            .on_action(cx.listener(Self::on_select_down))
+            .on_action(cx.listener(Self::on_select_all))
+            .on_action(cx.listener(Self::on_home))
+            .on_action(cx.listener(Self::on_end))
+            .on_action(cx.listener(Self::on_copy))
+            .on_action(cx.listener(Self::on_cut))
+            .on_action(cx.listener(Self::on_paste))
+            .on_action(cx.listener(Self::on_commit))
+            .on_action(cx.listener(Self::on_cancel))
+            .on_mouse_down(MouseButton::Left, cx.listener(Self::on_mouse_down))
+            .on_mouse_up(MouseButton::Left, cx.listener(Self::on_mouse_up))
+            .on_mouse_up_out(MouseButton::Left, cx.listener(Self::on_mouse_up))
+            .on_mouse_move(cx.listener(Self::on_mouse_move))
+            .child(RgTextElement { editor: cx.entity() })
+    }
+}
+
+// 标签只有一段，换行符替换为空格
+fn single_line(text: &str) -> String {
+    text.replace("\r\n", " ").replace(['\n', '\r'], " ")
+}
+
+fn utf16_to_utf8(text: &str, offset: usize) -> usize {
+    let mut utf16_count = 0;
+    for (index, c) in text.char_indices() {
+        if utf16_count >= offset {
+            return index;
+        }
+        utf16_count += c.len_utf16();
+    }
+    text.len()
+}
+
+// 折行后每一行的字节范围
+fn visual_lines(layout: &WrappedLine) -> Vec<Range<usize>> {
+    let mut starts = vec![0];
+    for boundary in layout.wrap_boundaries() {
+        starts.push(layout.runs()[boundary.run_ix].glyphs[boundary.glyph_ix].index);
+    }
+
+    let mut lines = Vec::with_capacity(starts.len());
+    for (row, start) in starts.iter().enumerate() {
+        let end = starts.get(row + 1).copied().unwrap_or(layout.len());
+        lines.push(*start..end);
+    }
+    lines
+}
+
+// 文字块的左上角，与 RgRect 的标签一样在节点内竖直居中
+fn text_origin(line_count: usize, bounds: Bounds<Pixels>, line_height: Pixels) -> Point<Pixels> {
+    let text_height = line_height * line_count as f32;
+    let top = ((bounds.size.height - text_height) / 2.0).max(px(0.0));
+    point(bounds.left(), bounds.top() + top)
+}
+
+// 行内 index 相对行首的水平位置
+fn x_in_line(layout: &WrappedLine, index: usize, line: &Range<usize>, line_height: Pixels) -> Pixels {
+    if index <= line.start {
+        return px(0.0);
+    }
+    layout.position_for_index(index, line_height).map_or(px(0.0), |position| position.x)
+}
+
+// 按对齐方式计算的行首水平偏移，与 WrappedLine::paint 的对齐方式一致
+fn line_offset(layout: &WrappedLine, line: &Range<usize>, width: Pixels, line_height: Pixels, align: RgTextAlign) -> Pixels {
+    let line_width = x_in_line(layout, line.end, line, line_height);
+    match align {
+        RgTextAlign::Left => px(0.0),
+        RgTextAlign::Center => (width - line_width) / 2.0,
+        RgTextAlign::Right => width - line_width,
+    }
+}
+
+// index 在指定行中的屏幕位置（行的左上角高度）
+fn position_in_line(
+    layout: &WrappedLine,
+    index: usize,
+    line: &Range<usize>,
+    bounds: Bounds<Pixels>,
+    line_height: Pixels,
+    align: RgTextAlign,
+) -> Option<Point<Pixels>> {
+    let lines = visual_lines(layout);
+    let row = lines.iter().position(|l| l == line)?;
+    let origin = text_origin(lines.len(), bounds, line_height);
+    let x = origin.x + line_offset(layout, line, bounds.size.width, line_height, align) + x_in_line(layout, index, line, line_height);
+    Some(point(x, origin.y + line_height * row as f32))
+}
+
+// 光标的屏幕位置，位于折行处时显示在上一行末尾
+fn caret_position(layout: &WrappedLine, index: usize, bounds: Bounds<Pixels>, line_height: Pixels, align: RgTextAlign) -> Option<Point<Pixels>> {
+    let lines = visual_lines(layout);
+    let line = lines.iter().find(|line| index <= line.end).or(lines.last())?;
+    position_in_line(layout, index, line, bounds, line_height, align)
+}